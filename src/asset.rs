@@ -0,0 +1,67 @@
+use std::env::consts;
+
+use crate::{
+    context::Context,
+    github,
+    version::{number, split_repo},
+};
+
+// the first release that ships the linux archives named after their architecture
+static ARCH_NAMING_SINCE: [u64; 3] = [0, 10, 4];
+
+// get the name of the release asset for this machine
+// a template from the options replaces the built-in naming, e.g. for forks
+pub fn asset_name(ctx: &Context, version: &str) -> Result<String, Box<dyn std::error::Error>> {
+    // only linux builds are supported
+    if consts::OS != "linux" {
+        return Err(format!("Unsupported operating system: {}", consts::OS).into());
+    }
+
+    // neovim names the architectures slightly differently
+    let arch = match consts::ARCH {
+        "x86_64" => "x86_64",
+        "aarch64" => "arm64",
+        arch => {
+            return Err(format!("Unsupported architecture: {}", arch).into());
+        }
+    };
+
+    // fill in the template when there is one
+    if let Some(template) = &ctx.asset_template {
+        return Ok(template
+            .replace("{os}", consts::OS)
+            .replace("{arch}", arch)
+            .replace("{version}", split_repo(version).1));
+    }
+
+    // older releases only have a single x86_64 archive
+    // named tags like stable and nightly always follow the current naming
+    match number(version) {
+        Some(number) if number < ARCH_NAMING_SINCE => {
+            if arch != "x86_64" {
+                return Err(format!("Version {} has no build for {}", version, arch).into());
+            }
+
+            Ok("nvim-linux64.tar.gz".to_string())
+        }
+        _ => Ok(format!("nvim-linux-{}.tar.gz", arch)),
+    }
+}
+
+// get the download urls of the release asset for this machine, one for every mirror in order
+// the mirrors only hold neovim itself, forks are downloaded from their releases
+pub fn asset_urls(ctx: &Context, version: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    // get the name of the asset
+    let name = asset_name(ctx, version)?;
+
+    let (repo, tag) = split_repo(version);
+    let bases = match repo {
+        Some(repo) => vec![github::download_url(&ctx.github_url, repo)],
+        None => ctx.base_urls.clone(),
+    };
+
+    Ok(bases
+        .iter()
+        .map(|base| format!("{}/{}/{}", base.trim_end_matches('/'), tag, name))
+        .collect())
+}
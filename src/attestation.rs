@@ -0,0 +1,65 @@
+// check the build provenance github attests for release assets
+// the sigstore bundles are fetched and verified by the github cli, which keeps up with the format
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use reqwest::Url;
+
+use crate::{
+    context::Context,
+    github::GITHUB_URL,
+    version::{split_repo, UPSTREAM_REPO},
+};
+
+// verify that the archive of the version was built by the workflows of its repository
+pub fn verify(
+    ctx: &Context,
+    version: &str,
+    archive: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !ctx.verify_attestation {
+        return Ok(());
+    }
+
+    let repo = split_repo(version).0.unwrap_or(UPSTREAM_REPO);
+
+    let mut command = Command::new("gh");
+    command
+        .args(["attestation", "verify"])
+        .arg(archive)
+        .args(["--repo", repo])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null());
+
+    // the cli asks the same server the releases come from, with the same token
+    if ctx.github_url.trim_end_matches('/') != GITHUB_URL {
+        if let Some(host) = Url::parse(&ctx.github_url)
+            .ok()
+            .and_then(|url| url.host_str().map(String::from))
+        {
+            command.env("GH_HOST", host);
+        }
+    }
+    if let Some(token) = &ctx.github_token {
+        command.env("GH_TOKEN", token);
+    }
+
+    match command.status() {
+        Ok(status) if status.success() => {
+            ctx.reporter
+                .info(&format!("Verified the attestation of version {}", version));
+
+            Ok(())
+        }
+        Ok(_) => Err(format!(
+            "Version {} has no valid attestation from {}, refusing it",
+            version, repo
+        )
+        .into()),
+        Err(_) => {
+            Err("Verifying attestations needs the GitHub CLI (gh), which is not installed".into())
+        }
+    }
+}
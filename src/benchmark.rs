@@ -0,0 +1,130 @@
+// compare the startup time of versions with the config of the user
+use std::{
+    env, fs,
+    path::Path,
+    process::{self, Command, Stdio},
+};
+
+use crate::{active_version, context::Context, extracted, paths};
+
+// the line --startuptime ends a measurement with
+static STARTED: &str = "NVIM STARTED";
+
+// start the nvim binary once and return how long it took to start in milliseconds
+fn startup_time(nvim: &Path, log: &Path) -> Result<f64, Box<dyn std::error::Error>> {
+    // nvim appends to the log, every run needs a fresh one
+    let _ = fs::remove_file(log);
+
+    let status = Command::new(nvim)
+        .arg("--headless")
+        .arg("--startuptime")
+        .arg(log)
+        .arg("+qa")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    if !status.is_ok_and(|status| status.success()) {
+        return Err("nvim did not start".into());
+    }
+
+    // the line looks like `041.875  000.002: --- NVIM STARTED ---`, the first column is the total
+    let content = fs::read_to_string(log).unwrap_or_default();
+    let _ = fs::remove_file(log);
+
+    content
+        .lines()
+        .rfind(|line| line.contains(STARTED))
+        .and_then(|line| line.split_whitespace().next())
+        .and_then(|time| time.parse().ok())
+        .ok_or_else(|| "nvim did not report its startup time".into())
+}
+
+// start every version a number of times and print the median, fastest and slowest startup
+// the versions default to every cached version
+pub fn benchmark(
+    ctx: &Context,
+    versions: &[String],
+    runs: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let versions = if versions.is_empty() {
+        paths::cached_versions()?
+    } else {
+        versions.to_vec()
+    };
+
+    if versions.is_empty() {
+        return Err("There are no cached versions to benchmark".into());
+    }
+
+    let log = env::temp_dir().join(format!("nvim_switcher-startuptime-{}", process::id()));
+    let active = active_version(ctx);
+
+    // the sorted startup times of every version that could be measured
+    let mut results: Vec<(String, Vec<f64>)> = Vec::new();
+    for version in &versions {
+        let nvim = extracted(ctx, version)?.join("bin/nvim");
+
+        ctx.reporter
+            .info(&format!("Starting {} {} times", version, runs));
+
+        let times: Result<Vec<f64>, _> = (0..runs).map(|_| startup_time(&nvim, &log)).collect();
+        match times {
+            Ok(mut times) => {
+                times.sort_by(f64::total_cmp);
+                results.push((version.clone(), times));
+            }
+            Err(error) => ctx
+                .reporter
+                .warn(&format!("Skipping {}: {}", version, error)),
+        }
+    }
+
+    if results.is_empty() {
+        return Err("None of the versions could be benchmarked".into());
+    }
+
+    // the active version is what the others are compared to
+    let median = |times: &[f64]| times[times.len() / 2];
+    let baseline = results
+        .iter()
+        .find(|(version, _)| Some(version) == active.as_ref())
+        .map(|(_, times)| median(times));
+
+    ctx.reporter.output(&format!(
+        "  {:<10} {:>10} {:>10} {:>10}{}",
+        "Version",
+        "Median",
+        "Min",
+        "Max",
+        if baseline.is_some() {
+            "  vs active"
+        } else {
+            ""
+        }
+    ));
+    for (version, times) in &results {
+        let compared = match baseline {
+            Some(baseline) if baseline > 0.0 => {
+                format!("  {:+.0}%", (median(times) / baseline - 1.0) * 100.0)
+            }
+            _ => String::new(),
+        };
+
+        ctx.reporter.output(&format!(
+            "{} {:<10} {:>8.1}ms {:>8.1}ms {:>8.1}ms{}",
+            if Some(version) == active.as_ref() {
+                "*"
+            } else {
+                " "
+            },
+            version,
+            median(times),
+            times[0],
+            times[times.len() - 1],
+            compared
+        ));
+    }
+
+    Ok(())
+}
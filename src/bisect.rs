@@ -0,0 +1,157 @@
+use std::{process::Command, rc::Rc};
+
+use crate::{context::Context, current, github, prompt, report::CaptureReporter, switch, version};
+
+// the verdict on a single version
+enum Verdict {
+    Good,
+    Bad,
+    Skip,
+}
+
+// find the first release between the good and the bad version that shows a regression
+// versions are judged by running the command, or by asking the user when there is none
+pub fn bisect(
+    ctx: &Context,
+    good: &str,
+    bad: &str,
+    command: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    // resolve the versions into the upstream tags
+    let good = version::resolve_version(ctx, good)?;
+    let bad = version::resolve_version(ctx, bad)?;
+
+    // order the releases from old to new, nightly is newer than any release
+    let mut versions: Vec<String> = github::releases(ctx)?
+        .into_iter()
+        .map(|release| release.tag)
+        .filter(|tag| version::number(tag).is_some())
+        .collect();
+    versions.sort_by_key(|tag| version::number(tag));
+    versions.push("nightly".to_string());
+
+    // find the bounds
+    let position = |tag: &str| versions.iter().position(|version| version == tag);
+    let (start, end) = match (position(&good), position(&bad)) {
+        (Some(start), Some(end)) if start < end => (start, end),
+        (Some(_), Some(_)) => {
+            return Err(format!(
+                "Good version {} is not older than bad version {}",
+                good, bad
+            )
+            .into());
+        }
+        (None, _) => return Err(format!("Version {} not found", good).into()),
+        (_, None) => return Err(format!("Version {} not found", bad).into()),
+    };
+
+    // remember the active version so we can switch back to it afterwards
+    let original = current(ctx).ok();
+
+    // the first entry is always good and the last always bad
+    let mut range = versions[start..=end].to_vec();
+    let mut skipped = Vec::new();
+
+    while range.len() > 2 {
+        let middle = range.len() / 2;
+        let candidate = range[middle].clone();
+
+        ctx.reporter.info(&format!(
+            "Bisecting: {} versions left to test, trying {}",
+            range.len() - 2,
+            candidate
+        ));
+
+        // the details of every switch are only of interest when it fails
+        let capture = Rc::new(CaptureReporter::default());
+        let quiet = Context {
+            reporter: capture.clone(),
+            ..ctx.clone()
+        };
+        if let Err(error) = switch(&quiet, &candidate) {
+            capture.replay(ctx.reporter.as_ref());
+
+            return Err(error);
+        }
+
+        match judge(ctx, &candidate, command)? {
+            // the regression is after the candidate
+            Verdict::Good => {
+                range.drain(..middle);
+            }
+            // the regression is the candidate or before it
+            Verdict::Bad => {
+                range.truncate(middle + 1);
+            }
+            // the candidate cannot be tested
+            Verdict::Skip => {
+                skipped.push(range.remove(middle));
+            }
+        }
+    }
+
+    ctx.reporter.success(&format!(
+        "{} is the first bad version, {} is the last good version",
+        range[1], range[0]
+    ));
+
+    // github only keeps the latest nightly build, so there is nothing in between to try
+    if range[1] == "nightly" {
+        ctx.reporter.info(&format!(
+            "The regression is in a nightly build after {}, older nightly builds are not kept upstream so it cannot be narrowed down further",
+            range[0]
+        ));
+    }
+
+    if !skipped.is_empty() {
+        ctx.reporter.warn(&format!(
+            "The skipped versions may hide the regression: {}",
+            skipped.join(", ")
+        ));
+    }
+
+    // switch back to the version that was active before bisecting
+    match original.map(|original| version::normalize(&original)) {
+        Some(Ok(original)) => switch(ctx, &original)?,
+        _ => ctx
+            .reporter
+            .info("Switch back to your version with `nvim_switcher switch VERSION`"),
+    }
+
+    Ok(())
+}
+
+// decide whether the active version is good or bad
+fn judge(
+    ctx: &Context,
+    version: &str,
+    command: &[String],
+) -> Result<Verdict, Box<dyn std::error::Error>> {
+    // run the command, exiting with 125 skips the version like git bisect does
+    if let Some((program, args)) = command.split_first() {
+        let status = Command::new(program).args(args).status()?;
+
+        let verdict = match status.code() {
+            Some(0) => Verdict::Good,
+            Some(125) => Verdict::Skip,
+            _ => Verdict::Bad,
+        };
+
+        return Ok(verdict);
+    }
+
+    // ask the user until we get a usable answer
+    loop {
+        let answer = prompt::ask(
+            ctx,
+            &format!("Is {} good or bad? [g]ood/[b]ad/[s]kip", version),
+        )?;
+
+        match answer.to_lowercase().as_str() {
+            "g" | "good" => return Ok(Verdict::Good),
+            "b" | "bad" => return Ok(Verdict::Bad),
+            "s" | "skip" => return Ok(Verdict::Skip),
+            _ => ctx.reporter.warn("Please answer good, bad or skip"),
+        }
+    }
+}
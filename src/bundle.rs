@@ -0,0 +1,210 @@
+// move cached versions to machines without internet access
+// a bundle is an uncompressed tar of the cached archives, their revisions and a bundle.toml listing them
+use std::{
+    fs::{self, File},
+    io,
+    path::Path,
+    time::SystemTime,
+};
+
+use crate::{check_archive, context::Context, format_size, paths, state, timestamp, toml, version};
+
+// the name of the file in the bundle listing its versions
+static METADATA: &str = "bundle.toml";
+
+// pack the cached versions into a bundle, every cached version by default
+pub fn export(
+    ctx: &Context,
+    file: &Path,
+    versions: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cached = paths::cached_versions()?;
+
+    // the files of forks are kept apart in the cache, which bundles do not carry yet
+    let versions: Vec<String> = if versions.is_empty() {
+        cached
+            .iter()
+            .filter(|version| version::split_repo(version).0.is_none())
+            .cloned()
+            .collect()
+    } else {
+        versions
+            .iter()
+            .map(|requested| version::resolve_version(ctx, requested))
+            .collect::<Result<_, _>>()?
+    };
+
+    if let Some(missing) = versions.iter().find(|version| !cached.contains(version)) {
+        return Err(format!("Version {} is not downloaded", missing).into());
+    }
+
+    if let Some(fork) = versions
+        .iter()
+        .find(|version| version::split_repo(version).0.is_some())
+    {
+        return Err(format!("Version {} is from a fork, which cannot be bundled", fork).into());
+    }
+
+    if versions.is_empty() {
+        return Err("There are no cached versions to bundle".into());
+    }
+
+    let failed = || format!("Failed to write {}", file.display());
+    let mut builder = tar::Builder::new(File::create(file).map_err(|_| failed())?);
+
+    // the list of versions comes first, so it can be checked before anything is imported
+    let metadata = format!(
+        "versions = [{}]\n",
+        versions
+            .iter()
+            .map(|version| toml::quote(version))
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+    let mut header = tar::Header::new_gnu();
+    header.set_size(metadata.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(timestamp::seconds(SystemTime::now()));
+    builder
+        .append_data(&mut header, METADATA, metadata.as_bytes())
+        .map_err(|_| failed())?;
+
+    let mut size = 0;
+    for version in &versions {
+        let archive = paths::archive(version)?;
+        size += fs::metadata(&archive)?.len();
+
+        builder
+            .append_path_with_name(&archive, format!("nvim-{}.tar.gz", version))
+            .map_err(|_| failed())?;
+
+        // the revision tells whether a newer nightly is out once the machine is online again
+        let revision = paths::revision(version)?;
+        if revision.exists() {
+            builder
+                .append_path_with_name(&revision, format!("nvim-{}.revision", version))
+                .map_err(|_| failed())?;
+        }
+
+        ctx.reporter.info(&format!("Bundled version {}", version));
+    }
+
+    builder.finish().map_err(|_| failed())?;
+
+    ctx.reporter.success(&format!(
+        "Wrote {} versions ({}) to {}",
+        versions.len(),
+        format_size(size),
+        file.display()
+    ));
+
+    Ok(())
+}
+
+// unpack a bundle into the cache, replacing the versions that are already cached
+pub fn import(ctx: &Context, file: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let open = || match File::open(file) {
+        Ok(file) => Ok(tar::Archive::new(file)),
+        Err(_) => Err(format!("Failed to open {}", file.display())),
+    };
+    let invalid = || format!("{} is not a bundle", file.display());
+
+    // read the list of versions
+    let mut versions = None;
+    for entry in open()?.entries().map_err(|_| invalid())? {
+        let mut entry = entry.map_err(|_| invalid())?;
+
+        if entry.path().ok().as_deref() == Some(Path::new(METADATA)) {
+            let mut content = String::new();
+            io::Read::read_to_string(&mut entry, &mut content).map_err(|_| invalid())?;
+
+            // the versions become file names in the cache, so they may not point anywhere else
+            versions = match toml::parse(&content)
+                .map_err(|_| invalid())?
+                .get("versions")
+            {
+                Some(toml::Value::Array(items)) => items
+                    .iter()
+                    .map(|item| match item {
+                        toml::Value::String(version)
+                            if !version.contains('/') && !version.starts_with('.') =>
+                        {
+                            Some(version.clone())
+                        }
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<String>>>(),
+                _ => None,
+            };
+
+            break;
+        }
+    }
+    let versions = versions.ok_or_else(invalid)?;
+
+    // only the files of the listed versions are unpacked, and only into the cache itself
+    let wanted = |name: &str| {
+        versions.iter().any(|version| {
+            name == format!("nvim-{}.tar.gz", version)
+                || name == format!("nvim-{}.revision", version)
+        })
+    };
+
+    let cache = paths::cache_dir()?;
+    let now = timestamp::seconds(SystemTime::now());
+    let mut imported = 0;
+    let mut size = 0;
+    for entry in open()?.entries().map_err(|_| invalid())? {
+        let mut entry = entry.map_err(|_| invalid())?;
+
+        let name = match entry
+            .path()
+            .ok()
+            .and_then(|path| path.to_str().map(String::from))
+        {
+            Some(name) if wanted(&name) => name,
+            _ => continue,
+        };
+
+        // write next to the final file and move it in place once it is complete
+        let path = cache.join(&name);
+        let partial = cache.join(format!("{}.partial", name));
+        let unpacked = File::create(&partial).and_then(|mut out| io::copy(&mut entry, &mut out));
+        if unpacked.is_err() {
+            let _ = fs::remove_file(&partial);
+
+            return Err(format!("Failed to unpack {}", name).into());
+        }
+
+        // a damaged archive should not replace a working one
+        if name.ends_with(".tar.gz") {
+            if let Err(error) = check_archive(&partial) {
+                let _ = fs::remove_file(&partial);
+
+                return Err(error);
+            }
+        }
+
+        fs::rename(&partial, &path)?;
+
+        if let Some(version) = name
+            .strip_prefix("nvim-")
+            .and_then(|name| name.strip_suffix(".tar.gz"))
+        {
+            state::record_download(version, now)?;
+            imported += 1;
+            size += entry.size();
+
+            ctx.reporter.info(&format!("Imported version {}", version));
+        }
+    }
+
+    ctx.reporter.success(&format!(
+        "Imported {} versions ({}) from {}",
+        imported,
+        format_size(size),
+        file.display()
+    ));
+
+    Ok(())
+}
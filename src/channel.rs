@@ -0,0 +1,134 @@
+// channels keep versions installed next to the active one, each reachable as nvim-NAME
+// e.g. nvim-stable and nvim-nightly, while nvim itself stays the version that was switched to
+use std::{fs, path::PathBuf};
+
+use crate::{
+    context::Context,
+    extracted,
+    links::{self, Link},
+    paths, resolve_version, state,
+};
+
+// get the symlink a channel is reachable as
+fn link_path(name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(paths::link_dir()?
+        .join("bin")
+        .join(format!("nvim-{}", name)))
+}
+
+// get the version a channel link points at, none when it is missing or not ours
+fn linked_version(name: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let target = match fs::read_link(link_path(name)?) {
+        Ok(target) => target,
+        Err(_) => return Ok(None),
+    };
+
+    // the versions of forks are extracted apart from those of neovim, so look for the directory
+    for version in paths::cached_versions()? {
+        if target.starts_with(paths::version_dir(&version)?) {
+            return Ok(Some(version));
+        }
+    }
+
+    Ok(None)
+}
+
+// extract the version the channel stands for and point its link at it
+fn link(ctx: &Context, name: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let version = resolve_version(ctx, name)?;
+    let dir = extracted(ctx, &version)?;
+
+    links::create(&Link {
+        link: link_path(name)?,
+        target: dir.join("bin/nvim"),
+    })?;
+
+    Ok(version)
+}
+
+// add a channel, the name is any version, e.g. stable, nightly or an alias
+// it is resolved again on every refresh, so stable follows the newest release
+pub fn add(ctx: &Context, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // the name ends up in the name of the link
+    if name.is_empty()
+        || name.starts_with('.')
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+    {
+        return Err(format!("Invalid channel: {}", name).into());
+    }
+
+    let version = link(ctx, name)?;
+    state::add_channel(name)?;
+
+    ctx.reporter.success(&format!(
+        "{} now runs version {}",
+        link_path(name)?.display(),
+        version
+    ));
+
+    Ok(())
+}
+
+// point every channel at the version it stands for now, downloading what is new
+pub fn refresh(ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    let channels = state::channels()?;
+    if channels.is_empty() {
+        ctx.reporter.info("No channels added");
+
+        return Ok(());
+    }
+
+    for name in &channels {
+        let before = linked_version(name)?;
+        let version = link(ctx, name)?;
+
+        if before.as_ref() == Some(&version) && name != "nightly" {
+            ctx.reporter
+                .info(&format!("nvim-{} is up to date at {}", name, version));
+        } else {
+            ctx.reporter
+                .success(&format!("nvim-{} now runs version {}", name, version));
+        }
+    }
+
+    Ok(())
+}
+
+// remove a channel and its link, the extracted version is left for purge
+pub fn remove(ctx: &Context, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // only remove the link when it is still one of ours
+    let path = link_path(name)?;
+    if linked_version(name)?.is_some() {
+        fs::remove_file(&path)?;
+    }
+
+    if state::remove_channel(name)? {
+        ctx.reporter.success(&format!("Removed channel {}", name));
+    } else {
+        ctx.reporter.info(&format!("There is no channel {}", name));
+    }
+
+    Ok(())
+}
+
+// list the channels with the version they run
+pub fn list(ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    let channels = state::channels()?;
+    if channels.is_empty() {
+        ctx.reporter.info("No channels added");
+    }
+
+    for name in channels {
+        let version = linked_version(&name)?;
+
+        ctx.reporter.output(&format!(
+            "nvim-{} -> {}",
+            name,
+            version.as_deref().unwrap_or("missing, run channel refresh")
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,174 @@
+// check downloaded archives against the sha-256 checksums published with the release
+use std::path::Path;
+
+use crate::{context::Context, github, http, sha256};
+
+// get a text file, none when the server does not have it
+fn text(ctx: &Context, url: &str) -> Option<String> {
+    let client = http::client(ctx).ok()?;
+    let response = http::retry(ctx, &format!("Failed to download {}", url), || {
+        http::send(ctx, http::authorize(ctx, client.get(url), url))
+    })
+    .ok()?;
+
+    if !response.status().is_success() {
+        return None;
+    }
+
+    response.text().ok()
+}
+
+// is the text a sha-256 digest in hex
+fn is_digest(text: &str) -> bool {
+    text.len() == 64 && text.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// read the checksum of a file published on its own, a single line looking like HASH  NAME, or the hash alone
+fn single(text: &str) -> Option<String> {
+    text.split_whitespace()
+        .next()
+        .filter(|hash| is_digest(hash))
+        .map(|hash| hash.to_lowercase())
+}
+
+// find the checksum of the named file in a list with a line for every file, like sha256sum writes
+// the name has a * in front when it was hashed in binary mode
+fn listed(text: &str, name: &str) -> Option<String> {
+    text.lines().find_map(|line| {
+        let (hash, file) = line.split_once(char::is_whitespace)?;
+
+        (is_digest(hash) && file.trim().trim_start_matches('*') == name)
+            .then(|| hash.to_lowercase())
+    })
+}
+
+// get the checksum published next to the file at the url as URL.EXTENSION, e.g. URL.sha256
+pub fn published(ctx: &Context, url: &str, extension: &str) -> Option<String> {
+    single(&text(ctx, &format!("{}.{}", url, extension))?)
+}
+
+// find the published checksum of the archive downloaded from the url
+// neovim publishes NAME.sha256sum next to its archives up to v0.10.3 and a shasum.txt covering all of
+// them since, both are read from the same mirror, the github api reports the digest of recent uploads
+fn expected(ctx: &Context, version: &str, url: &str) -> Option<String> {
+    let (dir, name) = url.rsplit_once('/')?;
+
+    let single = published(ctx, url, "sha256sum");
+
+    let listed = || listed(&text(ctx, &format!("{}/shasum.txt", dir))?, name);
+
+    let reported = || {
+        github::release(ctx, version)
+            .ok()?
+            .assets
+            .into_iter()
+            .find(|asset| asset.name == name)?
+            .digest?
+            .strip_prefix("sha256:")
+            .filter(|hash| is_digest(hash))
+            .map(String::from)
+    };
+
+    single
+        .or_else(listed)
+        .or_else(reported)
+        .map(|hash| hash.to_lowercase())
+}
+
+// compare the archive downloaded from the url to its published checksum
+pub fn verify(
+    ctx: &Context,
+    version: &str,
+    url: &str,
+    path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match sha256::file(path) {
+        Ok(actual) => check(ctx, version, url, &actual),
+        Err(_) => Err(format!("Failed to read {}", path.display()).into()),
+    }
+}
+
+// compare the digest of what was downloaded from the url to its published checksum
+// an archive without one is let through with a warning, mirrors do not always carry the checksum files
+pub fn check(
+    ctx: &Context,
+    version: &str,
+    url: &str,
+    actual: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let expected = match expected(ctx, version, url) {
+        Some(expected) => expected,
+        None => {
+            ctx.reporter.warn(&format!(
+                "No checksum published for version {}, the download could not be verified",
+                version
+            ));
+
+            return Ok(());
+        }
+    };
+
+    if actual != expected {
+        return Err(format!(
+            "The checksum of {} does not match, expected {} but got {}",
+            url, expected, actual
+        )
+        .into());
+    }
+
+    ctx.reporter
+        .info(&format!("Verified the checksum of version {}", version));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static HASH: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    #[test]
+    fn a_sha256sum_file_is_read() {
+        let name = "nvim-linux64.tar.gz";
+        for text in [
+            format!("{}  {}\n", HASH, name),
+            format!("{} *{}", HASH, name),
+            format!("{}\n", HASH.to_uppercase()),
+        ] {
+            assert_eq!(single(&text).as_deref(), Some(HASH), "{}", text);
+        }
+
+        assert_eq!(single(""), None);
+        assert_eq!(single("<html>Not Found</html>"), None);
+        assert_eq!(single(&format!("{}  {}", &HASH[1..], name)), None);
+    }
+
+    #[test]
+    fn a_shasum_txt_line_is_found_by_name() {
+        let other = "0".repeat(64);
+        let text = format!(
+            "{}  nvim-linux-arm64.tar.gz\n{} *nvim-linux-x86_64.tar.gz\n{}  nvim-macos-x86_64.tar.gz\n",
+            other,
+            HASH.to_uppercase(),
+            other
+        );
+
+        assert_eq!(
+            listed(&text, "nvim-linux-x86_64.tar.gz").as_deref(),
+            Some(HASH)
+        );
+        assert_eq!(
+            listed(&text, "nvim-linux-arm64.tar.gz").as_deref(),
+            Some(&*other)
+        );
+
+        // a name is only matched whole
+        assert_eq!(listed(&text, "nvim-linux-x86_64.tar"), None);
+        assert_eq!(listed(&text, "nvim-win64.zip"), None);
+        assert_eq!(
+            listed("not a checksum  nvim-win64.zip", "nvim-win64.zip"),
+            None
+        );
+    }
+}
@@ -0,0 +1,185 @@
+// show what differs between the builds of two versions, to see what switching gains or loses
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::{context::Context, extracted, github, resolve_version};
+
+// print the names of the api functions, one per line
+static API_FUNCTIONS: &str =
+    "lua for _, f in ipairs(vim.fn.api_info().functions) do io.stdout:write(f.name, '\\n') end";
+
+// what nvim --version tells about a build
+#[derive(Default)]
+struct Build {
+    version: String,
+    build_type: Option<String>,
+    luajit: Option<String>,
+    compiler: Option<String>,
+    features: Vec<String>,
+}
+
+// run the binary and return what it printed, failing when it did not run
+fn output(nvim: &Path, args: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
+    let output = Command::new(nvim)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!("Failed to run {}", nvim.display()).into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// read the build details from the output of nvim --version
+fn parse(output: &str) -> Build {
+    let mut build = Build::default();
+
+    for (index, line) in output.lines().map(str::trim).enumerate() {
+        // the first line looks like NVIM v0.10.1
+        if index == 0 {
+            build.version = line.strip_prefix("NVIM ").unwrap_or(line).to_string();
+        } else if let Some(build_type) = line.strip_prefix("Build type: ") {
+            build.build_type = Some(build_type.to_string());
+        } else if line.starts_with("LuaJIT ") || line.starts_with("Lua ") {
+            build.luajit = Some(line.to_string());
+        } else if let Some(compiler) = line.strip_prefix("Compiled by ") {
+            build.compiler = Some(compiler.to_string());
+        } else if let Some(features) = line.strip_prefix("Features: ") {
+            build.features = features.split_whitespace().map(String::from).collect();
+        }
+    }
+
+    build
+}
+
+// get the date a version was published, nightly is republished every day
+fn published(ctx: &Context, version: &str) -> String {
+    github::release(ctx, version)
+        .ok()
+        .and_then(|release| release.published_at)
+        .and_then(|published| published.split('T').next().map(String::from))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+// print the entries only one of the lists has
+fn difference(ctx: &Context, title: &str, a: (&str, &[String]), b: (&str, &[String])) {
+    let only = |from: &[String], other: &[String]| -> Vec<String> {
+        from.iter()
+            .filter(|item| !other.contains(item))
+            .cloned()
+            .collect()
+    };
+
+    for (version, items) in [(a.0, only(a.1, b.1)), (b.0, only(b.1, a.1))] {
+        if !items.is_empty() {
+            ctx.reporter.output(&format!(
+                "{} only in {}: {}",
+                title,
+                version,
+                items.join(" ")
+            ));
+        }
+    }
+}
+
+// compare the builds of two versions side by side, with api the functions of their api as well
+pub fn compare(
+    ctx: &Context,
+    first: &str,
+    second: &str,
+    api: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let versions = [resolve_version(ctx, first)?, resolve_version(ctx, second)?];
+
+    let mut builds = Vec::new();
+    let mut functions = Vec::new();
+    for version in &versions {
+        let nvim = extracted(ctx, version)?.join("bin/nvim");
+        builds.push(parse(&output(&nvim, &["--version"])?));
+
+        if api {
+            let names = output(
+                &nvim,
+                &["--headless", "--clean", "-c", API_FUNCTIONS, "+qa"],
+            )?;
+            functions.push(names.lines().map(String::from).collect::<Vec<String>>());
+        }
+    }
+
+    let none = || "-".to_string();
+    let rows = [
+        (
+            "Version",
+            builds[0].version.clone(),
+            builds[1].version.clone(),
+        ),
+        (
+            "Published",
+            published(ctx, &versions[0]),
+            published(ctx, &versions[1]),
+        ),
+        (
+            "Build type",
+            builds[0].build_type.clone().unwrap_or_else(none),
+            builds[1].build_type.clone().unwrap_or_else(none),
+        ),
+        (
+            "Lua",
+            builds[0].luajit.clone().unwrap_or_else(none),
+            builds[1].luajit.clone().unwrap_or_else(none),
+        ),
+        (
+            "Compiled by",
+            builds[0].compiler.clone().unwrap_or_else(none),
+            builds[1].compiler.clone().unwrap_or_else(none),
+        ),
+    ];
+
+    let width = rows
+        .iter()
+        .map(|(_, value, _)| value.len())
+        .chain([versions[0].len()])
+        .max()
+        .unwrap_or(0);
+
+    ctx.reporter.output(&format!(
+        "  {:<12} {:<width$}  {}",
+        "",
+        versions[0],
+        versions[1],
+        width = width
+    ));
+    for (title, a, b) in &rows {
+        ctx.reporter.output(&format!(
+            "{} {:<12} {:<width$}  {}",
+            if a == b { " " } else { "*" },
+            title,
+            a,
+            b,
+            width = width
+        ));
+    }
+
+    difference(
+        ctx,
+        "Features",
+        (&versions[0], &builds[0].features),
+        (&versions[1], &builds[1].features),
+    );
+
+    if api {
+        difference(
+            ctx,
+            "API functions",
+            (&versions[0], &functions[0]),
+            (&versions[1], &functions[1]),
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,382 @@
+// completion scripts generated from the command line definition
+use clap::Command;
+
+use crate::shell::{self, Shell};
+
+// the name the completions are registered for
+static PROGRAM: &str = "nvim_switcher";
+
+// the arguments that take a version, completed with the versions nvim_switcher knows about
+static VERSION_ARGS: [&str; 4] = ["version", "versions", "good", "bad"];
+
+// the command printing the versions, run by the scripts when completing one
+static VERSIONS: &str = "nvim_switcher complete-versions 2>/dev/null";
+
+// an option as the completion scripts need it
+struct Flag {
+    long: Option<String>,
+    short: Option<char>,
+    help: String,
+    takes_value: bool,
+    global: bool,
+}
+
+impl Flag {
+    // every way to write the option, e.g. --yes and -y
+    fn names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        if let Some(long) = &self.long {
+            names.push(format!("--{}", long));
+        }
+        if let Some(short) = self.short {
+            names.push(format!("-{}", short));
+        }
+
+        names
+    }
+}
+
+// a subcommand with its options and the values its arguments accept
+struct Subcommand {
+    name: String,
+    help: String,
+    flags: Vec<Flag>,
+    values: Vec<String>,
+
+    // whether one of the arguments is a version
+    versions: bool,
+}
+
+// get the first line of the help of a command or argument
+fn help(text: Option<&clap::builder::StyledStr>) -> String {
+    text.map(|text| text.to_string())
+        .unwrap_or_default()
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+// get the options of a command, global ones included once the command is built
+fn flags(command: &Command) -> Vec<Flag> {
+    command
+        .get_arguments()
+        .filter(|arg| !arg.is_positional() && !arg.is_hide_set())
+        .map(|arg| Flag {
+            long: arg.get_long().map(|long| long.to_string()),
+            short: arg.get_short(),
+            help: help(arg.get_help()),
+            takes_value: arg.get_action().takes_values(),
+            global: arg.is_global_set(),
+        })
+        .collect()
+}
+
+// read what the scripts need from the command line definition
+fn subcommands(command: &Command) -> Vec<Subcommand> {
+    command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set())
+        .map(|subcommand| Subcommand {
+            name: subcommand.get_name().to_string(),
+            help: help(subcommand.get_about()),
+            flags: flags(subcommand),
+            values: subcommand
+                .get_positionals()
+                .flat_map(|arg| arg.get_possible_values())
+                .filter(|value| !value.is_hide_set())
+                .map(|value| value.get_name().to_string())
+                .collect(),
+            versions: subcommand
+                .get_positionals()
+                .any(|arg| VERSION_ARGS.contains(&arg.get_id().as_str())),
+        })
+        .collect()
+}
+
+// generate the completion script for the shell
+pub fn completions(shell: Shell, mut command: Command) -> String {
+    // building propagates the global options and adds the help options
+    command.build();
+
+    let globals = flags(&command);
+    let subcommands = subcommands(&command);
+
+    // the options whose value is the next word, these are skipped when looking for the subcommand
+    let valued: Vec<String> = globals
+        .iter()
+        .chain(subcommands.iter().flat_map(|subcommand| &subcommand.flags))
+        .filter(|flag| flag.takes_value)
+        .flat_map(Flag::names)
+        .collect::<std::collections::BTreeSet<String>>()
+        .into_iter()
+        .collect();
+
+    match shell {
+        Shell::Bash => bash(&globals, &subcommands, &valued),
+        Shell::Zsh => zsh(&globals, &subcommands, &valued),
+        Shell::Fish => fish(&globals, &subcommands),
+        Shell::Powershell => powershell(&globals, &subcommands, &valued),
+    }
+}
+
+fn bash(globals: &[Flag], subcommands: &[Subcommand], valued: &[String]) -> String {
+    // the words offered at the top level and for every subcommand
+    let top: Vec<String> = subcommands
+        .iter()
+        .map(|subcommand| subcommand.name.clone())
+        .chain(globals.iter().flat_map(Flag::names))
+        .collect();
+
+    let mut cases = format!("    \"\") words=\"{}\" ;;\n", top.join(" "));
+    for subcommand in subcommands {
+        let words: Vec<String> = subcommand
+            .values
+            .iter()
+            .cloned()
+            .chain(subcommand.flags.iter().flat_map(Flag::names))
+            .collect();
+
+        let versions = if subcommand.versions {
+            format!(" $({})", VERSIONS)
+        } else {
+            String::new()
+        };
+
+        cases += &format!(
+            "    {}) words=\"{}{}\" ;;\n",
+            subcommand.name,
+            words.join(" "),
+            versions
+        );
+    }
+
+    format!(
+        r#"_{program}() {{
+  local cur="${{COMP_WORDS[COMP_CWORD]}}" prev="${{COMP_WORDS[COMP_CWORD-1]}}" sub="" words="" i
+  for ((i = 1; i < COMP_CWORD; i++)); do
+    case "${{COMP_WORDS[i]}}" in
+      {valued}) ((i++)) ;;
+      -*) ;;
+      *) sub="${{COMP_WORDS[i]}}"; break ;;
+    esac
+  done
+  case "$prev" in
+    {valued}) COMPREPLY=($(compgen -f -- "$cur")); return ;;
+  esac
+  case "$sub" in
+{cases}  esac
+  COMPREPLY=($(compgen -W "$words" -- "$cur"))
+}}
+complete -F _{program} {program}
+"#,
+        program = PROGRAM,
+        valued = valued.join("|"),
+        cases = cases,
+    )
+}
+
+fn zsh(globals: &[Flag], subcommands: &[Subcommand], valued: &[String]) -> String {
+    let quote = |value: &str| shell::quote(Shell::Zsh, value);
+
+    // entries for _describe are the name and its description separated by a colon
+    let options = |flags: &[Flag]| -> String {
+        flags
+            .iter()
+            .flat_map(|flag| {
+                flag.names()
+                    .into_iter()
+                    .map(|name| quote(&format!("{}:{}", name, flag.help)))
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    };
+
+    let commands = subcommands
+        .iter()
+        .map(|subcommand| quote(&format!("{}:{}", subcommand.name, subcommand.help)))
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    let mut cases = format!(
+        "    \"\")\n      local -a commands=({}) options=({})\n      _describe command commands\n      _describe option options\n      ;;\n",
+        commands,
+        options(globals)
+    );
+    for subcommand in subcommands {
+        let mut values = subcommand
+            .values
+            .iter()
+            .map(|value| quote(value))
+            .collect::<Vec<String>>();
+        if subcommand.versions {
+            values.push(format!("${{(f)\"$({})\"}}", VERSIONS));
+        }
+        let values = values.join(" ");
+
+        cases += &format!(
+            "    {})\n      local -a values=({}) options=({})\n      compadd -a values\n      _describe option options\n      ;;\n",
+            subcommand.name,
+            values,
+            options(&subcommand.flags)
+        );
+    }
+
+    format!(
+        r#"#compdef {program}
+_{program}() {{
+  local sub="" i
+  for ((i = 2; i < CURRENT; i++)); do
+    case "${{words[i]}}" in
+      {valued}) ((i++)) ;;
+      -*) ;;
+      *) sub="${{words[i]}}"; break ;;
+    esac
+  done
+  case "${{words[CURRENT-1]}}" in
+    {valued}) _files; return ;;
+  esac
+  case "$sub" in
+{cases}  esac
+}}
+# autoloaded from the fpath the file is the completion itself, sourced it registers it
+if [ "$funcstack[1]" = "_{program}" ]; then
+  _{program} "$@"
+else
+  compdef _{program} {program}
+fi
+"#,
+        program = PROGRAM,
+        valued = valued.join("|"),
+        cases = cases,
+    )
+}
+
+fn fish(globals: &[Flag], subcommands: &[Subcommand]) -> String {
+    let quote = |value: &str| shell::quote(Shell::Fish, value);
+
+    // a complete line for an option, only offered when the condition holds
+    let option = |flag: &Flag, condition: Option<&str>| {
+        let mut line = format!("complete -c {}", PROGRAM);
+        if let Some(condition) = condition {
+            line += &format!(" -n {}", quote(condition));
+        }
+        if let Some(long) = &flag.long {
+            line += &format!(" -l {}", long);
+        }
+        if let Some(short) = flag.short {
+            line += &format!(" -s {}", short);
+        }
+        if flag.takes_value {
+            line += " -r -F";
+        }
+
+        line + &format!(" -d {}\n", quote(&flag.help))
+    };
+
+    // files are only completed as the value of an option
+    let mut script = format!("complete -c {} -f\n", PROGRAM);
+    for flag in globals {
+        script += &option(flag, None);
+    }
+
+    for subcommand in subcommands {
+        script += &format!(
+            "complete -c {} -n __fish_use_subcommand -a {} -d {}\n",
+            PROGRAM,
+            subcommand.name,
+            quote(&subcommand.help)
+        );
+
+        // the global options are already offered everywhere
+        let condition = format!("__fish_seen_subcommand_from {}", subcommand.name);
+        for flag in subcommand.flags.iter().filter(|flag| !flag.global) {
+            script += &option(flag, Some(&condition));
+        }
+
+        if subcommand.versions {
+            script += &format!(
+                "complete -c {} -n {} -a {}\n",
+                PROGRAM,
+                quote(&condition),
+                quote(&format!("({})", VERSIONS))
+            );
+        }
+
+        if !subcommand.values.is_empty() {
+            script += &format!(
+                "complete -c {} -n {} -a {}\n",
+                PROGRAM,
+                quote(&condition),
+                quote(&subcommand.values.join(" "))
+            );
+        }
+    }
+
+    script
+}
+
+fn powershell(globals: &[Flag], subcommands: &[Subcommand], valued: &[String]) -> String {
+    let list = |words: &mut dyn Iterator<Item = String>| {
+        words
+            .map(|word| shell::quote(Shell::Powershell, &word))
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+
+    let mut cases = format!(
+        "        '' {{ @({}) }}\n",
+        list(
+            &mut subcommands
+                .iter()
+                .map(|subcommand| subcommand.name.clone())
+                .chain(globals.iter().flat_map(Flag::names))
+        )
+    );
+    for subcommand in subcommands {
+        // powershell has its own way of silencing errors
+        let versions = if subcommand.versions {
+            format!(" + @(& {} complete-versions 2>$null)", PROGRAM)
+        } else {
+            String::new()
+        };
+
+        cases += &format!(
+            "        '{}' {{ @({}){} }}\n",
+            subcommand.name,
+            list(
+                &mut subcommand
+                    .values
+                    .iter()
+                    .cloned()
+                    .chain(subcommand.flags.iter().flat_map(Flag::names))
+            ),
+            versions
+        );
+    }
+
+    format!(
+        r#"Register-ArgumentCompleter -Native -CommandName {program} -ScriptBlock {{
+    param($wordToComplete, $commandAst, $cursorPosition)
+    $words = @($commandAst.CommandElements | Select-Object -Skip 1 | ForEach-Object {{ $_.ToString() }})
+    $valued = @({valued})
+    $sub = ''
+    for ($i = 0; $i -lt $words.Count; $i++) {{
+        $word = $words[$i]
+        if ($i -eq $words.Count - 1 -and $word -eq $wordToComplete) {{ break }}
+        if ($valued -contains $word) {{ $i++ }}
+        elseif (-not $word.StartsWith('-')) {{ $sub = $word; break }}
+    }}
+    $candidates = switch ($sub) {{
+{cases}        default {{ @() }}
+    }}
+    $candidates | Where-Object {{ $_ -like "$wordToComplete*" }} | ForEach-Object {{
+        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)
+    }}
+}}
+"#,
+        program = PROGRAM,
+        valued = list(&mut valued.iter().cloned()),
+        cases = cases,
+    )
+}
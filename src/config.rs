@@ -0,0 +1,85 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{
+    paths,
+    toml::{self, Value},
+};
+
+// the settings read from config.toml in the config directory
+#[derive(Default)]
+pub struct Config {
+    values: HashMap<String, Value>,
+}
+
+impl Config {
+    // read the config file, a missing file is the same as an empty one
+    pub fn load() -> Result<Config, Box<dyn std::error::Error>> {
+        Config::load_file(&paths::config_dir()?.join("config.toml"))
+    }
+
+    // read a file in the config format, a missing file is the same as an empty one
+    pub fn load_file(path: &Path) -> Result<Config, Box<dyn std::error::Error>> {
+        // read the file
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(_) => return Ok(Config::default()),
+        };
+
+        match toml::parse(&content) {
+            Ok(values) => Ok(Config { values }),
+            Err(error) => Err(format!("Failed to read {}: {}", path.display(), error).into()),
+        }
+    }
+
+    // get every key that is set, keys in a section are prefixed like `section.key`
+    pub fn keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self.values.keys().map(|key| key.as_str()).collect();
+        keys.sort();
+
+        keys
+    }
+
+    // get a boolean setting
+    pub fn bool(&self, key: &str) -> Option<bool> {
+        match self.values.get(key) {
+            Some(Value::Bool(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    // get an integer setting
+    pub fn integer(&self, key: &str) -> Option<i64> {
+        match self.values.get(key) {
+            Some(Value::Integer(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    // get a string setting
+    pub fn string(&self, key: &str) -> Option<String> {
+        match self.values.get(key) {
+            Some(Value::String(value)) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    // get a list of strings, a single string is split on commas
+    pub fn strings(&self, key: &str) -> Option<Vec<String>> {
+        match self.values.get(key) {
+            Some(Value::String(value)) => Some(
+                value
+                    .split(',')
+                    .map(|item| item.trim().to_string())
+                    .collect(),
+            ),
+            Some(Value::Array(items)) => items
+                .iter()
+                .map(|item| match item {
+                    Value::String(item) => Some(item.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::{cell::Cell, path::PathBuf, rc::Rc, time::Duration};
+
+use crate::report::Reporter;
+
+// the options shared by all the commands
+#[derive(Clone)]
+pub struct Context {
+    // where everything the user is told goes
+    pub reporter: Rc<dyn Reporter>,
+
+    // where the extracted nvim tree lives, defaults to the cache when not set
+    pub install_dir: Option<PathBuf>,
+
+    // work from the cache alone, set by --offline or once the network turns out to be unreachable
+    // shared by the copies of the context, so every later request fails fast
+    pub offline: Rc<Cell<bool>>,
+
+    // only connect over ipv4
+    pub prefer_ipv4: bool,
+
+    // the proxy every request goes through, the environment decides when not set
+    pub proxy: Option<String>,
+
+    // pem files with root certificates trusted on top of those of the system
+    pub ca_certificates: Vec<PathBuf>,
+
+    // the minisign public key downloads are signed with
+    pub minisign_key: Option<String>,
+
+    // the file with the gpg public keys downloads are signed with
+    pub gpg_key: Option<PathBuf>,
+
+    // refuse downloads whose signature could not be verified
+    pub require_signed: bool,
+
+    // refuse downloads without a build provenance attestation from github
+    pub verify_attestation: bool,
+
+    // extract versions while they download when switching
+    pub stream: bool,
+
+    // keep the archive of a streamed version in the cache
+    pub keep_archive: bool,
+
+    // answer yes to every prompt
+    pub yes: bool,
+
+    // fail instead of prompting
+    pub no_input: bool,
+
+    // the name of the release asset, overriding the built-in naming
+    pub asset_template: Option<String>,
+
+    // the base urls to download releases from, tried in order
+    pub base_urls: Vec<String>,
+
+    // the github server forks are downloaded from
+    pub github_url: String,
+
+    // the github api the releases are listed from
+    pub api_url: String,
+
+    // the fork versions are looked up in, neovim itself when not set
+    pub repo: Option<String>,
+
+    // extra names linked to the nvim binary
+    pub bin_aliases: Vec<String>,
+
+    // the token sent to github, raising the rate limit of the api
+    pub github_token: Option<String>,
+
+    // the number of cached versions to keep after a download or switch, unlimited when not set
+    pub keep_versions: Option<usize>,
+
+    // how long connecting may take before giving up
+    pub connect_timeout: Duration,
+
+    // how long to wait for a response or the next part of its body before giving up
+    pub read_timeout: Duration,
+
+    // the number of times a request failing on the network or the server is repeated
+    pub retries: u32,
+
+    // the number of connections a large download is split over
+    pub connections: usize,
+
+    // the bytes per second downloads are kept under, unlimited when not set
+    pub limit_rate: Option<u64>,
+}
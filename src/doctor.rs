@@ -0,0 +1,167 @@
+use std::{env, fs, path::Path, process::Command};
+
+use crate::{context::Context, current, github, links, manifest::Manifest, paths};
+
+// the oldest glibc the official linux builds run on
+static MIN_GLIBC: [u64; 2] = [2, 31];
+
+// a check returns the problem and how to fix it when it fails
+type Check = fn(&Context) -> Result<(), String>;
+
+// check everything nvim_switcher depends on and explain how to fix what is broken
+pub fn doctor(ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    let checks: [(&str, Check); 6] = [
+        ("Cache directory", check_cache),
+        ("PATH", check_path),
+        ("Symlinks", check_links),
+        ("Active nvim", check_nvim),
+        ("glibc", check_glibc),
+        ("Network", check_network),
+    ];
+
+    // run every check, a failing one does not stop the others
+    let mut failed = 0;
+    for (name, check) in checks {
+        match check(ctx) {
+            Ok(()) => ctx.reporter.success(&format!("{}: ok", name)),
+            Err(problem) => {
+                ctx.reporter.error(&format!("{}: {}", name, problem));
+
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!("{} of {} checks failed", failed, checks.len()).into());
+    }
+
+    Ok(())
+}
+
+// the cache has to be writable to download and extract versions
+fn check_cache(_: &Context) -> Result<(), String> {
+    let dir = paths::cache_dir().map_err(|error| error.to_string())?;
+
+    // write and remove a file
+    let probe = dir.join(".doctor");
+    if fs::write(&probe, "")
+        .and_then(|_| fs::remove_file(&probe))
+        .is_err()
+    {
+        return Err(format!(
+            "{} is not writable, fix its permissions or set XDG_CACHE_HOME to a writable directory",
+            dir.display()
+        ));
+    }
+
+    Ok(())
+}
+
+// the linked binaries are only found when their directory is on the PATH
+fn check_path(_: &Context) -> Result<(), String> {
+    let bin = paths::link_dir()
+        .map_err(|error| error.to_string())?
+        .join("bin");
+
+    let path = env::var_os("PATH").unwrap_or_default();
+    if !env::split_paths(&path).any(|dir| same_dir(&dir, &bin)) {
+        return Err(format!(
+            "{} is not on your PATH, add `export PATH=\"{}:$PATH\"` to your shell profile",
+            bin.display(),
+            bin.display()
+        ));
+    }
+
+    Ok(())
+}
+
+// are the two paths the same directory, following symlinks when they exist
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+// every symlink of the active version should point into it
+fn check_links(_: &Context) -> Result<(), String> {
+    let manifest = match Manifest::load().map_err(|error| error.to_string())? {
+        Some(manifest) => manifest,
+        None => return Ok(()),
+    };
+
+    let broken = manifest
+        .links
+        .iter()
+        .filter(|link| !links::is_intact(link))
+        .count();
+
+    if broken > 0 {
+        return Err(format!(
+            "{} of {} symlinks are missing or broken, run `nvim_switcher repair`",
+            broken,
+            manifest.links.len()
+        ));
+    }
+
+    Ok(())
+}
+
+// the active version should run on this system
+fn check_nvim(ctx: &Context) -> Result<(), String> {
+    match current(ctx) {
+        Ok(version) if version == "None" => {
+            Err("No version installed, run `nvim_switcher switch stable`".to_string())
+        }
+        Ok(_) => Ok(()),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+// the official builds link against a minimum glibc
+fn check_glibc(_: &Context) -> Result<(), String> {
+    // the first line of ldd reports the glibc version, e.g. ldd (GNU libc) 2.39
+    let output = Command::new("ldd")
+        .arg("--version")
+        .output()
+        .map_err(|_| "Could not run ldd to determine the glibc version".to_string())?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let version = stdout
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().last())
+        .unwrap_or_default();
+
+    // parse the major and minor part
+    let mut parts = version.split('.').map(|part| part.parse::<u64>().ok());
+    let number = match (parts.next().flatten(), parts.next().flatten()) {
+        (Some(major), Some(minor)) => [major, minor],
+        _ => {
+            return Err(
+                "Could not determine the glibc version, systems without glibc need the AppImage or a distribution package"
+                    .to_string(),
+            )
+        }
+    };
+
+    if number < MIN_GLIBC {
+        return Err(format!(
+            "glibc {} is older than {}.{}, use the AppImage or an older version of nvim",
+            version, MIN_GLIBC[0], MIN_GLIBC[1]
+        ));
+    }
+
+    Ok(())
+}
+
+// releases are looked up and downloaded from github
+fn check_network(ctx: &Context) -> Result<(), String> {
+    github::ping(ctx).map_err(|error| {
+        format!(
+            "{}, check your connection or proxy, --prefer-ipv4 helps on networks with broken IPv6",
+            error
+        )
+    })
+}
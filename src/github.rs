@@ -0,0 +1,262 @@
+use std::fs;
+
+use reqwest::{blocking::Client, StatusCode};
+
+use crate::{
+    context::Context,
+    http, json, paths,
+    version::{self, UPSTREAM_REPO},
+};
+
+// the server neovim publishes its releases on
+pub static GITHUB_URL: &str = "https://github.com";
+
+// the api of github.com, which lives on a host of its own
+static GITHUB_API_URL: &str = "https://api.github.com";
+
+// the number of releases requested per page, the maximum github allows
+static PER_PAGE: usize = 100;
+
+// a file attached to a release
+pub struct Asset {
+    pub name: String,
+    pub updated_at: Option<String>,
+    pub digest: Option<String>,
+}
+
+impl Asset {
+    // identify the uploaded file, changes whenever the asset is replaced
+    // the digest is only reported for assets uploaded recently, older ones fall back to the upload time
+    pub fn revision(&self) -> Option<String> {
+        self.digest.clone().or_else(|| self.updated_at.clone())
+    }
+}
+
+// a release as reported by the github api
+pub struct Release {
+    pub tag: String,
+    pub prerelease: bool,
+    pub published_at: Option<String>,
+    pub body: Option<String>,
+    pub assets: Vec<Asset>,
+}
+
+// get the api of a github server, enterprise servers serve it under /api/v3
+pub fn api_url(github_url: &str) -> String {
+    let github_url = github_url.trim_end_matches('/');
+
+    if github_url == GITHUB_URL {
+        GITHUB_API_URL.to_string()
+    } else {
+        format!("{}/api/v3", github_url)
+    }
+}
+
+// get the base url the release assets of a repository on a github server are downloaded from
+pub fn download_url(github_url: &str, repo: &str) -> String {
+    format!(
+        "{}/{}/releases/download/",
+        github_url.trim_end_matches('/'),
+        repo
+    )
+}
+
+// get the api url of a repository, neovim itself when none is given
+fn repo_url(ctx: &Context, repo: Option<&str>) -> String {
+    format!(
+        "{}/repos/{}",
+        ctx.api_url.trim_end_matches('/'),
+        repo.unwrap_or(UPSTREAM_REPO)
+    )
+}
+
+// get all the published releases, newest first
+// with a fork selected its releases are listed instead, their tags named after the fork
+pub fn releases(ctx: &Context) -> Result<Vec<Release>, Box<dyn std::error::Error>> {
+    let client = http::client(ctx)?;
+
+    let mut releases = Vec::new();
+
+    // keep requesting pages until one is not full
+    for page in 1.. {
+        let url = format!(
+            "{}/releases?per_page={}&page={}",
+            repo_url(ctx, ctx.repo.as_deref()),
+            PER_PAGE,
+            page
+        );
+
+        // parse the page
+        let page = get(ctx, &client, &url)?;
+        let items = match page.as_array() {
+            Some(items) => items,
+            None => {
+                return Err("Unexpected response from GitHub".into());
+            }
+        };
+
+        releases.extend(
+            items
+                .iter()
+                .filter_map(|item| parse_release(item, ctx.repo.as_deref())),
+        );
+
+        if items.len() < PER_PAGE {
+            break;
+        }
+    }
+
+    // remember the tags so versions can be completed without a request, failing to is harmless
+    // only the tags of neovim itself are completed
+    if ctx.repo.is_some() {
+        return Ok(releases);
+    }
+    let tags: Vec<&str> = releases
+        .iter()
+        .map(|release| release.tag.as_str())
+        .collect();
+    if let Ok(path) = paths::release_tags() {
+        let _ = fs::write(path, tags.join("\n") + "\n");
+    }
+
+    Ok(releases)
+}
+
+// get the release of the version, which is looked up in its fork when it names one
+pub fn release(ctx: &Context, version: &str) -> Result<Release, Box<dyn std::error::Error>> {
+    let client = http::client(ctx)?;
+
+    let (repo, tag) = version::split_repo(version);
+    let url = format!("{}/releases/tags/{}", repo_url(ctx, repo), tag);
+
+    match parse_release(&get(ctx, &client, &url)?, repo) {
+        Some(release) => Ok(release),
+        None => Err(format!("Release {} not found on GitHub", version).into()),
+    }
+}
+
+// make sure the api can be reached
+pub fn ping(ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    let url = repo_url(ctx, ctx.repo.as_deref());
+    let request = http::client(ctx)?
+        .head(&url)
+        .header("User-Agent", "nvim_switcher");
+
+    // the failure tells a timeout, an untrusted certificate or the rate limit apart
+    match http::send(ctx, http::authorize(ctx, request, &url)) {
+        Ok(response) if response.status().is_success() => Ok(()),
+        Ok(response) => Err(format!("GitHub responded with {}", response.status()).into()),
+        Err(failure) => Err(format!("Failed to reach GitHub: {}", failure.message).into()),
+    }
+}
+
+// read the etag and body of the last response to a url, the etag is on the first line
+fn cached_response(url: &str) -> Option<(String, String)> {
+    let content = fs::read_to_string(paths::api_response(url).ok()?).ok()?;
+    let (etag, body) = content.split_once('\n')?;
+
+    Some((etag.to_string(), body.to_string()))
+}
+
+// request a json document from the api
+// the last response is sent back as its etag, github answers 304 without counting it towards the rate limit
+fn get(
+    ctx: &Context,
+    client: &Client,
+    url: &str,
+) -> Result<json::Value, Box<dyn std::error::Error>> {
+    let cached = cached_response(url);
+
+    let response = http::retry(ctx, "Failed to fetch the releases from GitHub", || {
+        // github refuses requests without a user agent
+        let mut request = client
+            .get(url)
+            .header("User-Agent", "nvim_switcher")
+            .header("Accept", "application/vnd.github+json");
+        if let Some((etag, _)) = &cached {
+            request = request.header("If-None-Match", etag);
+        }
+
+        http::send(ctx, http::authorize(ctx, request, url))
+    });
+
+    // the api is the only source of the releases
+    let response = match response {
+        Ok(response) => response,
+        Err(error) => {
+            http::detect_offline(ctx);
+
+            return Err(error.into());
+        }
+    };
+
+    // nothing changed since the cached response
+    if response.status() == StatusCode::NOT_MODIFIED {
+        if let Some((_, body)) = cached {
+            return json::parse(&body);
+        }
+    }
+
+    // was it successful
+    if !response.status().is_success() {
+        return Err(format!(
+            "Failed to fetch the releases from GitHub: {}",
+            response.status()
+        )
+        .into());
+    }
+
+    let etag = response
+        .headers()
+        .get("ETag")
+        .and_then(|etag| etag.to_str().ok())
+        .map(String::from);
+    let body = response.text()?;
+    let value = json::parse(&body)?;
+
+    // remember the response for the next request, failing to is harmless
+    if let (Some(etag), Ok(path)) = (etag, paths::api_response(url)) {
+        let _ = fs::write(path, etag + "\n" + &body);
+    }
+
+    Ok(value)
+}
+
+// read a release of the repository from its json, drafts are skipped
+fn parse_release(value: &json::Value, repo: Option<&str>) -> Option<Release> {
+    // only published releases are of interest
+    if value.get("draft").and_then(json::Value::as_bool) == Some(true) {
+        return None;
+    }
+
+    // get a string field
+    let string = |value: &json::Value, key: &str| {
+        value
+            .get(key)
+            .and_then(json::Value::as_str)
+            .map(|value| value.to_string())
+    };
+
+    Some(Release {
+        tag: version::qualify(repo, &string(value, "tag_name")?),
+        prerelease: value
+            .get("prerelease")
+            .and_then(json::Value::as_bool)
+            .unwrap_or(false),
+        published_at: string(value, "published_at"),
+        body: string(value, "body"),
+        assets: value
+            .get("assets")
+            .and_then(json::Value::as_array)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|asset| {
+                Some(Asset {
+                    name: string(asset, "name")?,
+                    updated_at: string(asset, "updated_at"),
+                    digest: string(asset, "digest"),
+                })
+            })
+            .collect(),
+    })
+}
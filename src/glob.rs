@@ -0,0 +1,59 @@
+// does the text match the shell style pattern
+// supports `*` for any sequence, `?` for any single character and `[...]` character sets
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    matches(&pattern, &text)
+}
+
+// is the pattern something that should be matched instead of taken literally
+pub fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+fn matches(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        // an empty pattern only matches an empty text
+        None => text.is_empty(),
+        // try every possible length for the wildcard
+        Some('*') => (0..=text.len()).any(|skip| matches(&pattern[1..], &text[skip..])),
+        // any single character
+        Some('?') => !text.is_empty() && matches(&pattern[1..], &text[1..]),
+        // a set of characters, taken literally when it is never closed
+        Some('[') => match pattern.iter().position(|&c| c == ']') {
+            Some(end) if end > 1 => {
+                !text.is_empty()
+                    && in_set(&pattern[1..end], text[0])
+                    && matches(&pattern[end + 1..], &text[1..])
+            }
+            _ => text.first() == Some(&'[') && matches(&pattern[1..], &text[1..]),
+        },
+        // a literal character
+        Some(c) => text.first() == Some(c) && matches(&pattern[1..], &text[1..]),
+    }
+}
+
+// is the character part of the set, e.g. `0-9` or `abc`, negated with a leading `!`
+fn in_set(set: &[char], c: char) -> bool {
+    // is the set negated
+    let (negated, set) = match set.first() {
+        Some('!') | Some('^') => (true, &set[1..]),
+        _ => (false, set),
+    };
+
+    // check every item and range in the set
+    let mut found = false;
+    let mut i = 0;
+    while i < set.len() {
+        if i + 2 < set.len() && set[i + 1] == '-' {
+            found |= set[i] <= c && c <= set[i + 2];
+            i += 3;
+        } else {
+            found |= set[i] == c;
+            i += 1;
+        }
+    }
+
+    found != negated
+}
@@ -0,0 +1,83 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    time::SystemTime,
+};
+
+use crate::{paths, timestamp};
+
+// a switch between two versions, as recorded in the history log
+pub struct Entry {
+    // seconds since the unix epoch
+    pub time: u64,
+
+    // the version that was active before, if any
+    pub from: Option<String>,
+
+    pub to: String,
+
+    // whether the switch succeeded
+    pub ok: bool,
+}
+
+// get the path to the history log
+fn path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(paths::data_dir()?.join("history"))
+}
+
+// append a switch to the log, entries are never rewritten
+pub fn record(from: Option<&str>, to: &str, ok: bool) -> Result<(), Box<dyn std::error::Error>> {
+    // every entry is a line of tab separated fields
+    let line = format!(
+        "{}\t{}\t{}\t{}\n",
+        timestamp::seconds(SystemTime::now()),
+        from.unwrap_or("-"),
+        to,
+        if ok { "ok" } else { "failed" }
+    );
+
+    // append to the file
+    let appended = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path()?)
+        .and_then(|mut file| file.write_all(line.as_bytes()));
+
+    if appended.is_err() {
+        return Err("Failed to write the history".into());
+    }
+
+    Ok(())
+}
+
+// read the log from old to new, there is none before the first switch
+pub fn entries() -> Result<Vec<Entry>, Box<dyn std::error::Error>> {
+    // read the file
+    let content = match fs::read_to_string(path()?) {
+        Ok(content) => content,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let mut entries = Vec::new();
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        let fields: Vec<&str> = line.split('\t').collect();
+
+        let entry = match fields[..] {
+            [time, from, to, outcome] => time.parse().ok().map(|time| Entry {
+                time,
+                from: Some(from.to_string()).filter(|from| from != "-"),
+                to: to.to_string(),
+                ok: outcome == "ok",
+            }),
+            _ => None,
+        };
+
+        match entry {
+            Some(entry) => entries.push(entry),
+            None => return Err("The history is corrupted".into()),
+        }
+    }
+
+    Ok(entries)
+}
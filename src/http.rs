@@ -0,0 +1,328 @@
+use std::{
+    fs, io,
+    net::{IpAddr, Ipv4Addr},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::{
+    blocking::{Client, RequestBuilder, Response},
+    Certificate, NoProxy, Proxy, StatusCode, Url,
+};
+
+use crate::{context::Context, timestamp};
+
+// how long connecting and waiting for data may take when not configured
+pub static DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+pub static DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+// the number of times a failed request is repeated when not configured
+pub static DEFAULT_RETRIES: u32 = 3;
+
+// the delay before the first retry, doubled for every retry after it
+pub static RETRY_DELAY: Duration = Duration::from_secs(1);
+
+// why an attempt failed, and whether trying again may help
+pub struct Failure {
+    pub message: String,
+    pub retryable: bool,
+}
+
+impl Failure {
+    // a failure that may pass, like a dropped connection or an overloaded server
+    pub fn retryable(message: impl Into<String>) -> Self {
+        Failure {
+            message: message.into(),
+            retryable: true,
+        }
+    }
+
+    // a failure that repeats on every attempt, like a missing file
+    pub fn permanent(message: impl Into<String>) -> Self {
+        Failure {
+            message: message.into(),
+            retryable: false,
+        }
+    }
+}
+
+// build the http client used for every request
+pub fn client(ctx: &Context) -> Result<Client, Box<dyn std::error::Error>> {
+    // nothing is requested without a network, rather than waiting for it to time out
+    if ctx.offline.get() {
+        return Err("Offline, only cached versions can be used".into());
+    }
+
+    // the timeout of the client applies to waiting for the response and every read of its body,
+    // so a slow but steady download is never cut off
+    let mut builder = Client::builder()
+        .connect_timeout(ctx.connect_timeout)
+        .timeout(ctx.read_timeout);
+
+    // the proxy from the options replaces the one from HTTP_PROXY and HTTPS_PROXY, which reqwest reads itself
+    // NO_PROXY still lists the hosts reached directly
+    if let Some(proxy) = &ctx.proxy {
+        match Proxy::all(proxy) {
+            Ok(proxy) => builder = builder.proxy(proxy.no_proxy(NoProxy::from_env())),
+            Err(_) => return Err(format!("Invalid proxy: {}", proxy).into()),
+        }
+    }
+
+    // extra roots for networks re-signing tls traffic with a certificate of their own,
+    // they are trusted next to the roots of the system
+    for path in &ctx.ca_certificates {
+        let pem = match fs::read(path) {
+            Ok(pem) => pem,
+            Err(_) => return Err(format!("Failed to read certificate {}", path.display()).into()),
+        };
+
+        let certificates = match Certificate::from_pem_bundle(&pem) {
+            Ok(certificates) if !certificates.is_empty() => certificates,
+            _ => {
+                return Err(format!("{} does not contain a PEM certificate", path.display()).into())
+            }
+        };
+
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    // binding to an ipv4 address makes the connector skip ipv6 addresses entirely
+    if ctx.prefer_ipv4 {
+        builder = builder.local_address(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+    }
+
+    match builder.build() {
+        Ok(client) => Ok(client),
+        Err(_) => Err("Failed to create the http client".into()),
+    }
+}
+
+// get the host of a url, none when it does not parse
+fn host(url: &str) -> Option<String> {
+    Url::parse(url).ok()?.host_str().map(String::from)
+}
+
+// add the token to a request to the api or one of the configured mirrors
+// any other host never sees it, reqwest also drops it when a redirect leaves the host
+pub fn authorize(ctx: &Context, request: RequestBuilder, url: &str) -> RequestBuilder {
+    let token = match &ctx.github_token {
+        Some(token) => token,
+        None => return request,
+    };
+
+    let trusted = host(url).is_some_and(|found| {
+        ctx.base_urls
+            .iter()
+            .chain([&ctx.api_url, &ctx.github_url])
+            .any(|base| host(base).as_ref() == Some(&found))
+    });
+
+    if trusted {
+        request.bearer_auth(token)
+    } else {
+        request
+    }
+}
+
+// what classifying the outcome of a request needs of the context
+// copied out of it, so the parts of a parallel download can classify theirs on their own threads
+#[derive(Clone, Copy)]
+pub struct Limits {
+    pub connect_timeout: Duration,
+    pub read_timeout: Duration,
+    // whether requests carry a token, an exhausted rate limit is only raised by setting one
+    pub authorized: bool,
+}
+
+impl Limits {
+    pub fn of(ctx: &Context) -> Self {
+        Limits {
+            connect_timeout: ctx.connect_timeout,
+            read_timeout: ctx.read_timeout,
+            authorized: ctx.github_token.is_some(),
+        }
+    }
+}
+
+// explain a response refused because the rate limit was hit, none for any other response
+fn rate_limited(limits: &Limits, response: &Response) -> Option<String> {
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from)
+    };
+
+    // github answers 403 for more than the rate limit, only an exhausted limit says remaining 0
+    let limited = match response.status() {
+        StatusCode::TOO_MANY_REQUESTS => true,
+        StatusCode::FORBIDDEN => header("x-ratelimit-remaining").as_deref() == Some("0"),
+        _ => false,
+    };
+    if !limited {
+        return None;
+    }
+
+    let mut message = "GitHub rate limit exceeded".to_string();
+    if let Some(reset) = header("x-ratelimit-reset").and_then(|reset| reset.parse().ok()) {
+        message += &format!(", it resets at {}", timestamp::format(reset));
+    }
+
+    if !limits.authorized {
+        message += ", set GITHUB_TOKEN (or github_token in the config) to raise the limit";
+    }
+
+    Some(message)
+}
+
+// does any of the errors causing the error mention one of the words, the libraries below reqwest
+// only tell what went wrong in their messages
+fn mentions(error: &reqwest::Error, words: &[&str]) -> bool {
+    let mut source = std::error::Error::source(error);
+    while let Some(error) = source {
+        let message = error.to_string().to_lowercase();
+        if words.iter().any(|word| message.contains(word)) {
+            return true;
+        }
+
+        source = error.source();
+    }
+
+    false
+}
+
+// send a request, see classify
+pub fn send(ctx: &Context, request: RequestBuilder) -> Result<Response, Failure> {
+    classify(&Limits::of(ctx), request.send())
+}
+
+// tell why a request failed, a response with any status but a server error is returned for the
+// caller to check
+pub fn classify(limits: &Limits, result: reqwest::Result<Response>) -> Result<Response, Failure> {
+    let response = match result {
+        Ok(response) => response,
+        Err(error) if error.is_connect() && error.is_timeout() => {
+            return Err(Failure::retryable(format!(
+                "timed out connecting after {}s",
+                limits.connect_timeout.as_secs()
+            )))
+        }
+        Err(error) if error.is_timeout() => {
+            return Err(Failure::retryable(format!(
+                "timed out waiting {}s for a response",
+                limits.read_timeout.as_secs()
+            )))
+        }
+        // a host that cannot be reached will not be within the retries, the next source may still be,
+        // see detect_offline for telling that there is no network at all
+        Err(error) if error.is_connect() && mentions(&error, &["dns error"]) => {
+            return Err(Failure::permanent("the host name could not be resolved"))
+        }
+        Err(error) if error.is_connect() && mentions(&error, &["network is unreachable"]) => {
+            return Err(Failure::permanent("the network is unreachable"))
+        }
+        Err(error) if error.is_connect() && mentions(&error, &["certificate"]) => {
+            return Err(Failure::permanent(
+                "the certificate of the server is not trusted, add its root with --ca-certificate",
+            ))
+        }
+        Err(error) if error.is_connect() => return Err(Failure::retryable("could not connect")),
+        Err(error) if error.is_builder() => return Err(Failure::permanent("invalid url")),
+        Err(_) => return Err(Failure::retryable("the connection failed")),
+    };
+
+    // too many requests passes unless it is the rate limit of github, which lasts up to an hour
+    let status = response.status();
+    if let Some(message) = rate_limited(limits, &response) {
+        return Err(Failure::permanent(message));
+    }
+    if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(Failure::retryable(format!(
+            "the server responded with {}",
+            status
+        )));
+    }
+
+    Ok(response)
+}
+
+// once every source failed, ask github whether there is a network at all
+// without one the rest of the run works offline, rather than failing on every request in turn
+pub fn detect_offline(ctx: &Context) {
+    let client = match client(ctx) {
+        Ok(client) => client,
+        Err(_) => return,
+    };
+
+    let unreachable = match client.head(&ctx.github_url).send() {
+        Ok(_) => false,
+        Err(error) => {
+            error.is_connect() && mentions(&error, &["dns error", "network is unreachable"])
+        }
+    };
+    if unreachable {
+        ctx.offline.set(true);
+        ctx.reporter
+            .warn("The network is unreachable, continuing with the cached versions");
+    }
+}
+
+// did reading the body of a response fail because no data arrived in time
+pub fn timed_out(error: &io::Error) -> bool {
+    error
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<reqwest::Error>())
+        .is_some_and(reqwest::Error::is_timeout)
+}
+
+// the delay before a retry, up to half the delay on top
+// the jitter is taken from the clock as nothing depends on it being random
+pub fn backoff(delay: Duration) -> Duration {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.subsec_nanos())
+        .unwrap_or(0);
+
+    delay + delay / 2 * (nanos % 1000) / 1000
+}
+
+// run the attempt until it succeeds, fails permanently or the retries run out
+// the delay doubles after every failure, with jitter so clients failing together do not retry together
+// what was attempted prefixes the reason it failed, e.g. Failed to download URL: could not connect
+pub fn retry<T>(
+    ctx: &Context,
+    what: &str,
+    mut attempt: impl FnMut() -> Result<T, Failure>,
+) -> Result<T, String> {
+    let mut delay = RETRY_DELAY;
+    let mut retry = 0;
+
+    loop {
+        let failure = match attempt() {
+            Ok(value) => return Ok(value),
+            Err(failure) => failure,
+        };
+
+        if !failure.retryable || retry >= ctx.retries {
+            return Err(format!("{}: {}", what, failure.message));
+        }
+
+        let wait = backoff(delay);
+
+        ctx.reporter.warn(&format!(
+            "{}: {}, retrying in {:.1}s ({} of {})",
+            what,
+            failure.message,
+            wait.as_secs_f64(),
+            retry + 1,
+            ctx.retries
+        ));
+
+        thread::sleep(wait);
+        delay *= 2;
+        retry += 1;
+    }
+}
@@ -0,0 +1,396 @@
+// a small json value, enough to read the github api and produce the machine readable output
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    // create an object from its fields
+    pub fn object(fields: Vec<(&str, Value)>) -> Value {
+        Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    // get a field of an object
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    // get the value as a string
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    // get the value as a boolean
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    // get the items of an array
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+// parse a json document
+pub fn parse(text: &str) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        position: 0,
+    };
+
+    let value = parser.value()?;
+
+    // there should be nothing but whitespace left
+    parser.whitespace();
+    if parser.position != parser.chars.len() {
+        return Err(parser.error());
+    }
+
+    Ok(value)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn error(&self) -> Box<dyn std::error::Error> {
+        format!("Invalid json at position {}", self.position).into()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.position += 1;
+        c
+    }
+
+    fn whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    // consume the expected word, e.g. true
+    fn word(&mut self, word: &str, value: Value) -> Result<Value, Box<dyn std::error::Error>> {
+        for expected in word.chars() {
+            if self.next() != Some(expected) {
+                return Err(self.error());
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Value, Box<dyn std::error::Error>> {
+        self.whitespace();
+
+        match self.peek() {
+            Some('n') => self.word("null", Value::Null),
+            Some('t') => self.word("true", Value::Bool(true)),
+            Some('f') => self.word("false", Value::Bool(false)),
+            Some('"') => Ok(Value::String(self.string()?)),
+            Some('[') => self.array(),
+            Some('{') => self.object(),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            _ => Err(self.error()),
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, Box<dyn std::error::Error>> {
+        let start = self.position;
+
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            self.position += 1;
+        }
+
+        let number: String = self.chars[start..self.position].iter().collect();
+        match number.parse() {
+            Ok(number) => Ok(Value::Number(number)),
+            Err(_) => Err(self.error()),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        // skip the opening quote
+        self.position += 1;
+
+        let mut result = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(result),
+                Some('\\') => match self.next() {
+                    Some('n') => result.push('\n'),
+                    Some('t') => result.push('\t'),
+                    Some('r') => result.push('\r'),
+                    Some('b') => result.push('\u{8}'),
+                    Some('f') => result.push('\u{c}'),
+                    Some('u') => {
+                        let mut code = self.hex()?;
+
+                        // characters outside the basic plane are written as a surrogate pair
+                        // a half without the other is replaced, like any other invalid character
+                        if (0xd800..0xdc00).contains(&code) {
+                            code = match self.low_surrogate() {
+                                Some(low) => 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00),
+                                None => 0xfffd,
+                            };
+                        }
+
+                        result.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                    }
+                    Some(c) => result.push(c),
+                    None => return Err(self.error()),
+                },
+                Some(c) => result.push(c),
+                None => return Err(self.error()),
+            }
+        }
+    }
+
+    // read the escape of the low half of a surrogate pair, only consumed when it is one
+    fn low_surrogate(&mut self) -> Option<u32> {
+        let start = self.position;
+
+        if self.next() == Some('\\') && self.next() == Some('u') {
+            if let Ok(low) = self.hex() {
+                if (0xdc00..0xe000).contains(&low) {
+                    return Some(low);
+                }
+            }
+        }
+
+        self.position = start;
+
+        None
+    }
+
+    // read the four hex digits of a unicode escape
+    fn hex(&mut self) -> Result<u32, Box<dyn std::error::Error>> {
+        let mut code = 0;
+
+        for _ in 0..4 {
+            match self.next().and_then(|c| c.to_digit(16)) {
+                Some(digit) => code = code * 16 + digit,
+                None => return Err(self.error()),
+            }
+        }
+
+        Ok(code)
+    }
+
+    fn array(&mut self) -> Result<Value, Box<dyn std::error::Error>> {
+        // skip the opening bracket
+        self.position += 1;
+
+        let mut items = Vec::new();
+
+        self.whitespace();
+        if self.peek() == Some(']') {
+            self.position += 1;
+
+            return Ok(Value::Array(items));
+        }
+
+        loop {
+            items.push(self.value()?);
+
+            self.whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Value::Array(items)),
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, Box<dyn std::error::Error>> {
+        // skip the opening brace
+        self.position += 1;
+
+        let mut fields = Vec::new();
+
+        self.whitespace();
+        if self.peek() == Some('}') {
+            self.position += 1;
+
+            return Ok(Value::Object(fields));
+        }
+
+        loop {
+            // the key
+            self.whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error());
+            }
+            let key = self.string()?;
+
+            // the separator
+            self.whitespace();
+            if self.next() != Some(':') {
+                return Err(self.error());
+            }
+
+            // the value
+            fields.push((key, self.value()?));
+
+            self.whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Value::Object(fields)),
+                _ => return Err(self.error()),
+            }
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Value {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Value {
+        Value::String(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Value {
+        Value::Bool(value)
+    }
+}
+
+impl From<u64> for Value {
+    fn from(value: u64) -> Value {
+        Value::Number(value as f64)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Value {
+        match value {
+            Some(value) => value.into(),
+            None => Value::Null,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(value) => write!(f, "{}", value),
+            // whole numbers are written without a fraction
+            Value::Number(value) if value.fract() == 0.0 && value.abs() < 1e15 => {
+                write!(f, "{}", *value as i64)
+            }
+            Value::Number(value) => write!(f, "{}", value),
+            Value::String(value) => write_string(f, value),
+            Value::Array(items) => {
+                write!(f, "[")?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(fields) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+// write a string with the characters json requires to be escaped
+fn write_string(f: &mut fmt::Formatter, value: &str) -> fmt::Result {
+    write!(f, "\"")?;
+
+    for c in value.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+
+    write!(f, "\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(text: &str) -> String {
+        parse(text).unwrap().as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn surrogate_pairs_are_combined() {
+        assert_eq!(string(r#""\ud83d\ude00""#), "\u{1f600}");
+        assert_eq!(string(r#""a\ud83d\ude00b""#), "a\u{1f600}b");
+    }
+
+    #[test]
+    fn a_lone_high_surrogate_is_replaced() {
+        assert_eq!(string(r#""\ud800ab""#), "\u{fffd}ab");
+        assert_eq!(string(r#""\ud800""#), "\u{fffd}");
+        assert_eq!(string(r#""\ud800\n""#), "\u{fffd}\n");
+    }
+
+    #[test]
+    fn a_bad_low_surrogate_is_kept() {
+        assert_eq!(string(r#""\ud800\u0041""#), "\u{fffd}A");
+        assert_eq!(string(r#""\ud800\ud800\udc00""#), "\u{fffd}\u{10000}");
+        assert_eq!(string(r#""\udc00x""#), "\u{fffd}x");
+    }
+}
@@ -0,0 +1,162 @@
+use std::{
+    fs, os,
+    path::{Path, PathBuf},
+};
+
+// a symlink placed by nvim_switcher, pointing into the extracted version
+pub struct Link {
+    pub link: PathBuf,
+    pub target: PathBuf,
+}
+
+// determine the symlinks for the extracted version in dir
+// bin and lib entries are linked directly, share entries one level deeper
+// so other applications can keep their own files in e.g. ~/.local/share/man
+// every alias gets an extra link to the nvim binary, e.g. vim
+pub fn plan(
+    dir: &Path,
+    link_dir: &Path,
+    aliases: &[String],
+) -> Result<Vec<Link>, Box<dyn std::error::Error>> {
+    let mut links = Vec::new();
+
+    // link all the files and folders in bin and lib
+    for name in ["bin", "lib"] {
+        entries(&dir.join(name), &link_dir.join(name), &mut links)?;
+    }
+
+    // loop over the share directory and link all the files and folders inside of it
+    for entry in fs::read_dir(dir.join("share"))? {
+        let entry = entry?;
+
+        // a plain file is linked directly
+        let output = link_dir.join("share").join(entry.file_name());
+        if !entry.file_type()?.is_dir() {
+            links.push(Link {
+                link: output,
+                target: entry.path(),
+            });
+
+            continue;
+        }
+
+        entries(&entry.path(), &output, &mut links)?;
+    }
+
+    // link the aliases to the nvim binary
+    for alias in aliases {
+        if alias.is_empty() || alias.contains('/') || alias == "nvim" {
+            return Err(format!("Invalid binary alias: {}", alias).into());
+        }
+
+        links.push(Link {
+            link: link_dir.join("bin").join(alias),
+            target: dir.join("bin/nvim"),
+        });
+    }
+
+    Ok(links)
+}
+
+// plan symlinks for all the files and folders in the directory
+fn entries(
+    original: &Path,
+    output: &Path,
+    links: &mut Vec<Link>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // loop over the original directory
+    for entry in fs::read_dir(original)? {
+        let entry = entry?;
+
+        // the symlink has the same name as the original
+        links.push(Link {
+            link: output.join(entry.file_name()),
+            target: entry.path(),
+        });
+    }
+
+    Ok(())
+}
+
+// create the symlink, replacing whatever file or link was there before
+pub fn create(link: &Link) -> Result<(), Box<dyn std::error::Error>> {
+    // create the output directory if it does not exist
+    if let Some(parent) = link.link.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // does the file already exist, this includes dangling symlinks
+    if link.link.symlink_metadata().is_ok() {
+        // remove the file
+        fs::remove_file(&link.link)?;
+    }
+
+    // create the symlink
+    os::unix::fs::symlink(&link.target, &link.link)?;
+
+    Ok(())
+}
+
+// does the symlink exist and point to an existing target
+pub fn is_intact(link: &Link) -> bool {
+    match fs::read_link(&link.link) {
+        Ok(target) => target == link.target && link.target.exists(),
+        Err(_) => false,
+    }
+}
+
+// remove the symlink, but only when it still points where we left it
+pub fn remove(link: &Link) -> Result<bool, Box<dyn std::error::Error>> {
+    match fs::read_link(&link.link) {
+        Ok(target) if target == link.target => {
+            fs::remove_file(&link.link)?;
+
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+// find the symlinks in the locations we link into that point into one of the owned directories
+// links of other applications are left alone
+pub fn find(link_dir: &Path, owned: &[PathBuf]) -> Result<Vec<Link>, Box<dyn std::error::Error>> {
+    // the same locations as plan uses
+    let mut dirs = vec![
+        link_dir.join("bin"),
+        link_dir.join("lib"),
+        link_dir.join("share"),
+    ];
+    if let Ok(entries) = fs::read_dir(link_dir.join("share")) {
+        for entry in entries {
+            let entry = entry?;
+
+            // the share directories themselves, not the ones we linked
+            if entry.file_type()?.is_dir() {
+                dirs.push(entry.path());
+            }
+        }
+    }
+
+    let mut links = Vec::new();
+    for dir in dirs {
+        // a location that does not exist has nothing to find
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for entry in entries {
+            let link = entry?.path();
+
+            if let Ok(target) = fs::read_link(&link) {
+                if owned.iter().any(|dir| target.starts_with(dir)) {
+                    links.push(Link { link, target });
+                }
+            }
+        }
+    }
+
+    links.sort_by(|a, b| a.link.cmp(&b.link));
+
+    Ok(links)
+}
@@ -1,333 +1,3781 @@
 use std::{
+    cell::Cell,
+    collections::HashSet,
     env,
-    fs::{self, File},
-    io::BufReader,
-    os,
-    path::Path,
-    process::Command,
+    fs::{self, File, OpenOptions},
+    io::{BufReader, BufWriter, Read, Write},
+    os::unix::{fs::MetadataExt, process::CommandExt},
+    path::{Component, Path, PathBuf},
+    process::{self, Command},
+    rc::Rc,
+    time::{Duration, SystemTime},
 };
 
-use clap::{Parser, Subcommand};
+use asset::{asset_name, asset_urls};
+use clap::{CommandFactory, Parser, Subcommand};
+use config::Config;
+use context::Context;
 use flate2::bufread::GzDecoder;
-use tar::Archive;
+use glob::{glob_match, is_glob};
+use manifest::Manifest;
+use progress::Progress;
+use report::{QuietReporter, Reporter, StdoutReporter};
+use shell::Shell;
+use tar::{Archive, EntryType};
+use throttle::Throttle;
+use version::{resolve_version, UPSTREAM_REPO};
+
+mod asset;
+mod attestation;
+mod benchmark;
+mod bisect;
+mod bundle;
+mod channel;
+mod checksum;
+mod compare;
+mod completions;
+mod config;
+mod context;
+mod doctor;
+mod github;
+mod glob;
+mod history;
+mod http;
+mod json;
+mod links;
+mod manifest;
+mod manpage;
+mod matrix;
+mod migrate;
+mod notify;
+mod parallel;
+mod paths;
+mod profile;
+mod progress;
+mod project;
+mod prompt;
+mod report;
+mod requirement;
+mod self_update;
+mod sha256;
+mod shell;
+mod shim;
+mod signature;
+mod state;
+mod stream;
+mod sync;
+mod systemd;
+mod throttle;
+mod timestamp;
+mod toml;
+mod tui;
+mod verify;
+mod version;
+mod watch;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
     #[command(subcommand)]
     cmd: Commands,
+
+    /// Directory the nvim files are extracted to, symlinks still point into ~/.local
+    #[arg(long, global = true, env = "NVIM_SWITCHER_INSTALL_DIR")]
+    install_dir: Option<PathBuf>,
+
+    /// Let me know when a newer nvim_switcher is available (checked at most once a day)
+    #[arg(long, global = true, env = "NVIM_SWITCHER_CHECK_UPDATE")]
+    check_update: bool,
+
+    /// Never touch the network, only cached versions can be used
+    #[arg(long, global = true, env = "NVIM_SWITCHER_OFFLINE")]
+    offline: bool,
+
+    /// Only connect over IPv4, for networks with broken IPv6 routes
+    #[arg(long, global = true)]
+    prefer_ipv4: bool,
+
+    /// Proxy to send every request through, e.g. http://proxy.example.com:3128, instead of HTTPS_PROXY
+    #[arg(long, global = true, value_name = "URL")]
+    proxy: Option<String>,
+
+    /// PEM file with root certificates to trust next to those of the system, may be repeated
+    #[arg(long = "ca-certificate", global = true, value_name = "FILE")]
+    ca_certificates: Vec<PathBuf>,
+
+    /// Minisign public key the downloads are signed with, checked against NAME.minisig next to the archive
+    #[arg(long, global = true, value_name = "KEY")]
+    minisign_key: Option<String>,
+
+    /// File with the gpg public keys the downloads are signed with, checked against NAME.asc next to the archive
+    #[arg(long, global = true, value_name = "FILE")]
+    gpg_key: Option<PathBuf>,
+
+    /// Refuse downloads whose signature could not be verified
+    #[arg(long, global = true)]
+    require_signed: bool,
+
+    /// Refuse downloads without a build provenance attestation from GitHub, checked with the GitHub CLI
+    #[arg(long, global = true)]
+    verify_attestation: bool,
+
+    /// Extract a version while it downloads when switching, instead of storing its archive first
+    #[arg(long, global = true)]
+    stream: bool,
+
+    /// Keep the archive in the cache when streaming
+    #[arg(long, global = true)]
+    keep_archive: bool,
+
+    /// Answer yes to every confirmation prompt
+    #[arg(short, long, global = true)]
+    yes: bool,
+
+    /// Fail instead of prompting when a confirmation is required
+    #[arg(long, global = true, conflicts_with = "yes")]
+    no_input: bool,
+
+    /// Name of the release asset to download, with {os}, {arch} and {version} placeholders
+    #[arg(long, global = true, value_name = "TEMPLATE")]
+    asset_template: Option<String>,
+
+    /// Comma separated base urls of mirrors to download from, tried in order
+    #[arg(
+        long,
+        global = true,
+        env = "NVIM_SWITCHER_BASE_URLS",
+        value_delimiter = ',',
+        value_name = "URLS"
+    )]
+    base_urls: Vec<String>,
+
+    /// Fork to download versions from instead of neovim/neovim, e.g. owner/neovim
+    #[arg(
+        long,
+        global = true,
+        env = "NVIM_SWITCHER_REPO",
+        value_name = "OWNER/NAME"
+    )]
+    repo: Option<String>,
+
+    /// Url of the GitHub server the releases are published on, e.g. a GitHub Enterprise instance
+    #[arg(
+        long,
+        global = true,
+        env = "NVIM_SWITCHER_GITHUB_URL",
+        value_name = "URL"
+    )]
+    github_url: Option<String>,
+
+    /// Url of the GitHub api the releases are listed from, derived from --github-url when not set
+    #[arg(long, global = true, env = "NVIM_SWITCHER_API_URL", value_name = "URL")]
+    api_url: Option<String>,
+
+    /// Extra names to link to the nvim binary when switching, e.g. vim, may be repeated
+    #[arg(long = "bin-alias", global = true, value_name = "NAME")]
+    bin_aliases: Vec<String>,
+
+    /// Keep only this many cached versions, removing the least recently downloaded after a download or switch
+    #[arg(long, global = true, value_name = "COUNT", value_parser = clap::value_parser!(u64).range(1..))]
+    keep_versions: Option<u64>,
+
+    /// How long connecting may take, e.g. 10s or 1m
+    #[arg(long, global = true, value_name = "DURATION", value_parser = parse_timeout)]
+    connect_timeout: Option<u64>,
+
+    /// How long to wait for a response or the next part of a download, e.g. 30s or 2m
+    #[arg(long, global = true, value_name = "DURATION", value_parser = parse_timeout)]
+    read_timeout: Option<u64>,
+
+    /// Number of times a request failing on the network or the server is repeated before giving up
+    #[arg(long, global = true, value_name = "COUNT")]
+    retries: Option<u32>,
+
+    /// Number of connections a large download is split over, 1 to download over a single one
+    #[arg(long, global = true, value_name = "COUNT", value_parser = clap::value_parser!(u16).range(1..))]
+    connections: Option<u16>,
+
+    /// Highest speed to download at in bytes per second, e.g. 500k or 2M
+    #[arg(long, global = true, value_name = "RATE", value_parser = parse_rate)]
+    limit_rate: Option<u64>,
+
+    /// Only print errors, warnings and the data a command was asked for
+    #[arg(short, long, global = true)]
+    quiet: bool,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum Commands {
+    Download {
+        /// The versions to download, e.g. v0.9.5 v0.10.4 nightly
+        #[arg(required = true)]
+        versions: Vec<String>,
+    },
+    Switch {
+        /// The version to switch to, - for the previous one, read from the closest .nvim-version file when left out
+        #[arg()]
+        version: Option<String>,
+
+        /// Pin the version so it is kept in the cache
+        #[arg(long)]
+        pin: bool,
+
+        /// Fail instead of offering a list to pick from when there is no version to switch to
+        #[arg(long)]
+        no_interactive: bool,
+
+        /// Extract the version again and recreate its symlinks, even when it is already in use or locked
+        #[arg(long)]
+        force: bool,
+    },
+    /// Extract the active version, or the given one, again and recreate its symlinks
+    Reinstall {
+        #[arg()]
+        version: Option<String>,
+    },
+    /// List the content of the archive of a version without extracting it
+    Inspect {
+        #[arg()]
+        version: String,
+
+        /// Download the version first when it is not in the cache
+        #[arg(long)]
+        download: bool,
+    },
+    /// List the downloaded versions
+    List {
+        /// Print the versions as json, including their size and download date
+        #[arg(long)]
+        json: bool,
+
+        /// List the releases available for download instead
+        #[arg(long)]
+        remote: bool,
+    },
+    /// Show the active version, the newest release, the cache and any problems at a glance
+    Status,
+    /// Show the release notes of a version
+    Changelog {
+        #[arg()]
+        version: String,
+
+        /// Show the notes of every release after this version up to the given one, the active version by default
+        #[arg(long, value_name = "VERSION", num_args = 0..=1, default_missing_value = "")]
+        since: Option<String>,
+    },
+    /// Compare the build type, Lua version, compiled features and release dates of two versions
+    Compare {
+        #[arg()]
+        first: String,
+
+        #[arg()]
+        second: String,
+
+        /// Also list the API functions only one of them has
+        #[arg(long)]
+        api: bool,
+    },
+    /// Show what is known about a version, locally and on GitHub
+    Info {
+        #[arg()]
+        version: String,
+    },
+    /// Show the disk space used by every cached version and the extracted install
+    Du {
+        /// Print the usage as json
+        #[arg(long)]
+        json: bool,
+    },
+    /// Compare the active version to the newest stable release and nightly build
+    Outdated {
+        /// Print the comparison as json
+        #[arg(long)]
+        json: bool,
+    },
+    /// Give a version a name that can be used wherever a version is accepted, or list the aliases
+    Alias {
+        #[arg()]
+        name: Option<String>,
+
+        /// The version the name stands for, e.g. v0.9.5 or stable
+        #[arg(conflicts_with = "remove")]
+        version: Option<String>,
+
+        /// Remove the alias instead
+        #[arg(long, requires = "name")]
+        remove: bool,
+    },
+    /// Keep versions installed next to the active one, reachable as nvim-stable, nvim-nightly and so on
+    Channel {
+        #[command(subcommand)]
+        action: ChannelAction,
+    },
+    /// Replace the nvim symlink with a script picking the version from NVIM_SWITCHER_VERSION or .nvim-version
+    Shim {
+        #[command(subcommand)]
+        action: ShimAction,
+    },
+    /// Pair a version with an NVIM_APPNAME, run it with run NAME
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+    /// Protect a cached version from being removed
+    Pin {
+        #[arg()]
+        version: String,
+    },
+    /// Remove the protection of a cached version
+    Unpin {
+        #[arg()]
+        version: String,
+    },
+    /// Set the version restore switches to when the install is lost, or show it
+    Default {
+        #[arg(conflicts_with = "unset")]
+        version: Option<String>,
+
+        /// Remove the default version
+        #[arg(long)]
+        unset: bool,
+    },
+    /// Switch to the default version when the installed nvim is missing, e.g. after the cache was cleared
+    Restore,
+    Current {
+        /// Exit with status 0 if this version is active and 1 otherwise, printing nothing
+        #[arg(long, value_name = "VERSION")]
+        is: Option<String>,
+
+        /// Print the result of --is as well
+        #[arg(short, long, requires = "is")]
+        verbose: bool,
+
+        /// Print the version, channel, install directory and binary as json
+        #[arg(long, conflicts_with = "is")]
+        json: bool,
+    },
+    Purge {
+        /// Versions or patterns like 'v0.9.*' to remove, picked from a list when left out
+        #[arg(conflicts_with_all = ["all", "unused", "older_than"])]
+        versions: Vec<String>,
+
+        /// Remove every cached version
+        #[arg(long)]
+        all: bool,
+
+        /// Keep the archive of the active version when removing all of them
+        #[arg(long, requires = "all")]
+        keep_active: bool,
+
+        /// Remove every cached version except the active one, the same as --all --keep-active
+        #[arg(long, conflicts_with = "all")]
+        unused: bool,
+
+        /// Remove the cached versions downloaded longer ago than this, e.g. 30d, 12h or 2w
+        #[arg(long, value_name = "DURATION", conflicts_with_all = ["all", "unused"])]
+        older_than: Option<String>,
+
+        /// Fail instead of offering a list to pick from when no version is given
+        #[arg(long)]
+        no_interactive: bool,
+    },
+    /// Switch to the newest stable release, downloading it when needed
+    Update {
+        /// Only report whether an update is available, exiting with status 10 when it is
+        #[arg(long)]
+        check: bool,
+
+        /// Also show a desktop notification the first time a release is found to be available
+        #[arg(long, requires = "check")]
+        notify: bool,
+
+        /// Switch even when the install is locked
+        #[arg(long)]
+        force: bool,
+    },
+    /// Freeze the active version, switching refuses to change it until unlock
+    Lock {
+        /// Why the version is locked, shown when a switch is refused
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Allow switching to other versions again
+    Unlock,
+    /// Remove the oldest snapshots of earlier nightly builds, e.g. nightly-2024-06-01
+    PruneNightlies {
+        /// How many of the newest snapshots to keep
+        #[arg(long)]
+        keep: usize,
+    },
+    /// Switch back to the version that was active before the last switch
+    Rollback {
+        /// Switch to the version of this entry of the history instead
+        #[arg(long, value_name = "ENTRY")]
+        to: Option<usize>,
+    },
+    /// Show the switches made so far, numbered for rollback --to
+    History,
+    /// Remove the broken symlinks left in ~/.local, e.g. for files a newer version dropped
+    Clean,
+    /// Remove every symlink, download and state of nvim_switcher, leaving only the config
+    Uninstall,
+    /// Check the setup and explain how to fix what is broken
+    Doctor,
+    /// Recreate missing or broken symlinks of the active version
+    Repair,
+    /// Import the nvim installs of bob, AppImages and tarballs extracted by hand into the cache
+    Migrate {
+        /// Directories or AppImages to import instead of looking in the usual places
+        #[arg()]
+        paths: Vec<PathBuf>,
+
+        /// Delete the originals once they are imported
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Compare the installed files to the cached archive they were extracted from
+    Verify {
+        /// Extract the version again when files are missing or modified
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Run a version of nvim once without switching to it, e.g. run v0.9.5 -- file.txt
+    Run {
+        /// The version, or a profile to run its version with its NVIM_APPNAME
+        #[arg()]
+        version: String,
+
+        /// Arguments passed to nvim
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    /// Run a version of nvim with an empty throwaway config, to tell a broken build from a broken config
+    Try {
+        #[arg()]
+        version: String,
+
+        /// Arguments passed to nvim
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    /// Run a command with a version of nvim first on the PATH, e.g. exec nightly -- make test
+    Exec {
+        #[arg()]
+        version: String,
+
+        /// The command to run and its arguments
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Print the path to the nvim binary of the active version, or of the given installed version
+    Which {
+        #[arg()]
+        version: Option<String>,
+    },
+    /// Print the urls of the archive that would be downloaded for a version, one per mirror
+    Url {
+        #[arg()]
+        version: String,
+    },
+    /// Find the release that introduced a regression between a good and a bad version
+    Bisect {
+        /// The good and the bad version, unless given with --good and --bad
+        #[arg(value_name = "VERSION", num_args = 0..=2)]
+        versions: Vec<String>,
+
+        /// The last version known to be good
+        #[arg(long, value_name = "VERSION")]
+        good: Option<String>,
+
+        /// The first version known to be bad, e.g. nightly
+        #[arg(long, value_name = "VERSION")]
+        bad: Option<String>,
+
+        /// Command that judges each version: exit 0 for good, 125 to skip and anything else for bad
+        #[arg(last = true)]
+        command: Vec<String>,
+    },
+    /// Compare the startup time of versions with your config, every cached version by default
+    Benchmark {
+        #[arg()]
+        versions: Vec<String>,
+
+        /// How many times to start every version
+        #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..))]
+        runs: u64,
+    },
+    /// Download the versions and set the pins, aliases and default declared in nvim-switcher.toml
+    Sync {
+        /// The file to sync from, ./nvim-switcher.toml or the one in the config directory by default
+        #[arg(long, value_name = "FILE")]
+        file: Option<PathBuf>,
+
+        /// Also remove the cached versions the file does not mention, except the active one
+        #[arg(long)]
+        prune: bool,
+    },
+    /// Write the cached versions, pins, aliases, default and active version to a file sync and import read
+    Export {
+        /// The file to write, printed when left out
+        #[arg()]
+        file: Option<PathBuf>,
+    },
+    /// Set up the versions of a file written by export, downloading what is missing
+    Import {
+        #[arg()]
+        file: PathBuf,
+    },
+    /// Move cached versions to a machine without internet access
+    Bundle {
+        #[command(subcommand)]
+        action: BundleAction,
+    },
+    /// Keep checking for a new nightly build and download it, until stopped
+    Watch {
+        /// How long to wait between checks, e.g. 30m, 6h or 1d
+        #[arg(long, value_name = "DURATION", default_value = "6h")]
+        interval: String,
+
+        /// Also switch to every new nightly build
+        #[arg(long)]
+        switch: bool,
+    },
+    /// Check for updates on a schedule with a user level systemd timer
+    Systemd {
+        #[command(subcommand)]
+        action: SystemdAction,
+    },
+    /// Run a command once for every cached version with it first on the PATH, e.g. for plugin CI
+    Matrix {
+        /// Comma separated versions to run the command for instead of every cached one
+        #[arg(long, value_delimiter = ',', value_name = "VERSIONS")]
+        versions: Vec<String>,
+
+        /// The command to run, e.g. nvim --headless +checkhealth +qa
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Print a snippet for your shell rc that switches to the .nvim-version of a project when entering it
+    Hook {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Print the commands that put the installed nvim on your PATH, e.g. `eval "$(nvim_switcher env)"`
+    Env {
+        /// The shell to print the commands for, detected from $SHELL when left out
+        #[arg(value_enum)]
+        shell: Option<Shell>,
+    },
+    /// Print the completion script for your shell
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Show a dashboard of the installed and remote versions to download, switch, pin and purge from
+    Tui,
+    /// Write man pages for nvim_switcher and its commands to a directory, e.g. ~/.local/share/man/man1
+    Manpage {
+        #[arg()]
+        dir: PathBuf,
+    },
+    /// Print the versions offered when completing a version, used by the completion scripts
+    #[command(hide = true)]
+    CompleteVersions,
+    /// Update nvim_switcher itself to the latest release
+    SelfUpdate {
+        /// Only report whether an update is available
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum BundleAction {
+    /// Pack cached versions into a single file, every cached version by default
+    Export {
+        #[arg()]
+        file: PathBuf,
+
+        #[arg()]
+        versions: Vec<String>,
+    },
+    /// Unpack the versions of a bundle into the cache, without downloading anything
+    Import {
+        #[arg()]
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum ChannelAction {
+    /// Install a version as nvim-VERSION, e.g. add stable for nvim-stable
+    Add {
+        #[arg()]
+        version: String,
+    },
+    /// Point every channel at the newest version it stands for, e.g. a new stable release
+    Refresh,
+    /// Remove a channel and its nvim-VERSION link
+    Remove {
+        #[arg()]
+        version: String,
+    },
+    /// List the channels with the version they run
+    List,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum ShimAction {
+    /// Put the shim in place of ~/.local/bin/nvim
+    Install,
+    /// Put the nvim symlink back
+    Uninstall,
+    /// Run the version the shim selects, used by the shim itself
+    #[command(hide = true)]
+    Exec {
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum ProfileAction {
+    /// Create or replace a profile
+    Add {
+        #[arg()]
+        name: String,
+
+        /// The version to run, e.g. v0.9.5 or nightly
+        #[arg(long)]
+        version: String,
+
+        /// The NVIM_APPNAME to run it with, the name of the profile by default
+        #[arg(long)]
+        appname: Option<String>,
+    },
+    /// Remove a profile, leaving its config alone
+    Remove {
+        #[arg()]
+        name: String,
+    },
+    /// List the profiles
+    List,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum SystemdAction {
+    /// Write and enable a service and timer, reporting a new stable release by default
+    Install {
+        /// When to run, in the OnCalendar format of systemd, e.g. hourly or Mon *-*-* 09:00
+        #[arg(long, default_value = "daily")]
+        schedule: String,
+
+        /// Download the newest nightly build instead
+        #[arg(long)]
+        nightly: bool,
+
+        /// Switch to a new stable release instead of only reporting it
+        #[arg(long, conflicts_with = "nightly")]
+        switch: bool,
+
+        /// Show a desktop notification when a new stable release is available
+        #[arg(long, conflicts_with_all = ["nightly", "switch"])]
+        notify: bool,
+    },
+    /// Disable the timer and remove the units
+    Uninstall,
+}
+
+fn main() {
+    // parse the arguments
+    let args = Args::parse();
+
+    // read the config file
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}", error);
+
+            process::exit(1);
+        }
+    };
+
+    // quiet only reports what was asked for
+    let reporter: Rc<dyn Reporter> = if args.quiet {
+        Rc::new(QuietReporter)
+    } else {
+        Rc::new(StdoutReporter)
+    };
+
+    // a timeout from the config is checked like the one from the flag
+    let timeout = |flag: Option<u64>, key: &str, default: Duration| match flag {
+        Some(seconds) => Duration::from_secs(seconds),
+        None => match config.string(key).map(|text| parse_timeout(&text)) {
+            Some(Ok(seconds)) => Duration::from_secs(seconds),
+            Some(Err(error)) => {
+                eprintln!("Invalid {} in the config: {}", key, error);
+
+                process::exit(1);
+            }
+            None => default,
+        },
+    };
+    let connect_timeout = timeout(
+        args.connect_timeout,
+        "connect_timeout",
+        http::DEFAULT_CONNECT_TIMEOUT,
+    );
+    let read_timeout = timeout(
+        args.read_timeout,
+        "read_timeout",
+        http::DEFAULT_READ_TIMEOUT,
+    );
+
+    // the rate in the config is a plain number of bytes or a size like the flag takes
+    let limit_rate = args.limit_rate.or_else(|| {
+        let rate = match config.string("limit_rate") {
+            Some(text) => parse_rate(&text),
+            None => match config.integer("limit_rate") {
+                Some(rate) => parse_rate(&rate.to_string()),
+                None => return None,
+            },
+        };
+
+        match rate {
+            Ok(rate) => Some(rate),
+            Err(error) => {
+                eprintln!("Invalid limit_rate in the config: {}", error);
+
+                process::exit(1);
+            }
+        }
+    });
+
+    // the download and api urls follow the server unless given themselves
+    let github_url = args
+        .github_url
+        .or_else(|| config.string("github_url"))
+        .unwrap_or_else(|| github::GITHUB_URL.to_string());
+
+    // collect the options shared by the commands
+    let ctx = Context {
+        reporter,
+        install_dir: args.install_dir,
+        offline: Rc::new(Cell::new(
+            args.offline || config.bool("offline").unwrap_or(false),
+        )),
+        prefer_ipv4: args.prefer_ipv4 || config.bool("prefer_ipv4").unwrap_or(false),
+        proxy: args.proxy.or_else(|| config.string("proxy")),
+        ca_certificates: Some(args.ca_certificates)
+            .filter(|certificates| !certificates.is_empty())
+            .or_else(|| {
+                config
+                    .strings("ca_certificates")
+                    .map(|paths| paths.into_iter().map(PathBuf::from).collect())
+            })
+            .unwrap_or_default(),
+        minisign_key: args.minisign_key.or_else(|| config.string("minisign_key")),
+        gpg_key: args
+            .gpg_key
+            .or_else(|| config.string("gpg_key").map(PathBuf::from)),
+        require_signed: args.require_signed || config.bool("require_signed").unwrap_or(false),
+        verify_attestation: args.verify_attestation
+            || config.bool("verify_attestation").unwrap_or(false),
+        stream: args.stream || config.bool("stream").unwrap_or(false),
+        keep_archive: args.keep_archive || config.bool("keep_archive").unwrap_or(false),
+        yes: args.yes,
+        no_input: args.no_input,
+        asset_template: args
+            .asset_template
+            .or_else(|| config.string("asset_template")),
+        base_urls: Some(args.base_urls)
+            .filter(|urls| !urls.is_empty())
+            .or_else(|| config.strings("base_urls"))
+            .unwrap_or_else(|| vec![github::download_url(&github_url, UPSTREAM_REPO)]),
+        api_url: args
+            .api_url
+            .or_else(|| config.string("api_url"))
+            .unwrap_or_else(|| github::api_url(&github_url)),
+        github_url,
+        repo: args
+            .repo
+            .or_else(|| config.string("repo"))
+            .filter(|repo| repo != UPSTREAM_REPO),
+        bin_aliases: Some(args.bin_aliases)
+            .filter(|aliases| !aliases.is_empty())
+            .or_else(|| config.strings("bin_aliases"))
+            .unwrap_or_default(),
+        github_token: env::var("GITHUB_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty())
+            .or_else(|| config.string("github_token")),
+        keep_versions: args
+            .keep_versions
+            .or_else(|| {
+                config
+                    .integer("keep_versions")
+                    .and_then(|count| u64::try_from(count).ok())
+                    .filter(|count| *count > 0)
+            })
+            .map(|count| count as usize),
+        retries: args
+            .retries
+            .or_else(|| {
+                config
+                    .integer("retries")
+                    .and_then(|count| u32::try_from(count).ok())
+            })
+            .unwrap_or(http::DEFAULT_RETRIES),
+        connections: args
+            .connections
+            .map(usize::from)
+            .or_else(|| {
+                config
+                    .integer("connections")
+                    .and_then(|count| usize::try_from(count).ok())
+                    .filter(|count| *count > 0)
+            })
+            .unwrap_or(parallel::DEFAULT_CONNECTIONS),
+        limit_rate,
+        connect_timeout,
+        read_timeout,
+    };
+
+    // the repository becomes part of the paths in the cache
+    if let Some(repo) = ctx
+        .repo
+        .as_deref()
+        .filter(|repo| !version::is_valid_repo(repo))
+    {
+        ctx.reporter.error(&format!(
+            "Invalid repository: {} (expected owner/name)",
+            repo
+        ));
+
+        process::exit(1);
+    }
+
+    // run the command
+    if let Err(error) = run(&ctx, args.cmd) {
+        ctx.reporter.error(&error.to_string());
+
+        process::exit(1);
+    }
+
+    // let the user know about a newer nvim_switcher, this should never get in the way
+    if args.check_update {
+        let _ = self_update::check_update(&ctx);
+    }
+}
+
+// parse a timeout given as a duration like 30s or 2m
+fn parse_timeout(text: &str) -> Result<u64, String> {
+    match timestamp::parse_duration(text) {
+        Ok(0) => Err("The timeout must be longer than 0s".to_string()),
+        Ok(seconds) => Ok(seconds),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+// parse a download speed like 800, 500k or 2M into bytes per second
+fn parse_rate(text: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid rate: {} (expected e.g. 500k or 2M)", text);
+
+    // a unit of the size may follow, optionally as KiB, KB or k/s
+    let lower = text.trim().to_lowercase();
+    let lower = lower.strip_suffix("/s").unwrap_or(&lower);
+    let lower = lower
+        .strip_suffix("ib")
+        .or_else(|| lower.strip_suffix('b'))
+        .unwrap_or(lower);
+    let (number, unit) = match lower.char_indices().last() {
+        Some((index, 'k')) => (&lower[..index], 1024),
+        Some((index, 'm')) => (&lower[..index], 1024 * 1024),
+        Some((index, 'g')) => (&lower[..index], 1024 * 1024 * 1024),
+        _ => (lower, 1),
+    };
+
+    match number.trim().parse::<f64>() {
+        Ok(number) if number.is_finite() && number * unit as f64 >= 1.0 => {
+            Ok((number * unit as f64) as u64)
+        }
+        Ok(_) => Err("The rate must be at least 1 byte per second".to_string()),
+        Err(_) => Err(invalid()),
+    }
+}
+
+// run the command
+fn run(ctx: &Context, cmd: Commands) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        Commands::Download { versions } => download_all(ctx, &versions)?,
+        Commands::Switch {
+            version,
+            pin: keep,
+            no_interactive,
+            force,
+        } => {
+            // fall back to the version of the project we are in
+            let version = match version {
+                Some(version) => version,
+                None => match project::version()? {
+                    Some((file, version)) => {
+                        ctx.reporter.info(&format!(
+                            "Using version {} from {}",
+                            version,
+                            file.display()
+                        ));
+
+                        version
+                    }
+                    None if no_interactive => {
+                        return Err("No version given and no .nvim-version file found".into());
+                    }
+                    None => pick_version(ctx, "Switch to:", true)?,
+                },
+            };
+
+            // like cd -, flip back to the version that was active before the last switch
+            let version = match version.as_str() {
+                "-" => match state::previous()? {
+                    Some(previous) => previous,
+                    None => return Err("No previous version to switch back to".into()),
+                },
+                _ => version,
+            };
+
+            switch_version(ctx, &version, force)?;
+
+            // pin the version once the switch succeeded
+            if keep {
+                pin(ctx, &version)?;
+            }
+        }
+        Commands::Reinstall { version } => {
+            let version = match version.or_else(|| active_version(ctx)) {
+                Some(version) => version,
+                None => return Err("No version installed".into()),
+            };
+
+            // reinstalling is not a way around the lock
+            check_lock(&resolve_version(ctx, &version)?)?;
+
+            switch_version(ctx, &version, true)?
+        }
+        Commands::Compare { first, second, api } => compare::compare(ctx, &first, &second, api)?,
+        Commands::Inspect {
+            version,
+            download: download_missing,
+        } => inspect(ctx, &version, download_missing)?,
+        Commands::List {
+            json,
+            remote: false,
+        } => list(ctx, json)?,
+        Commands::List { json, remote: true } => list_remote(ctx, json)?,
+        Commands::Status => status(ctx)?,
+        Commands::Changelog { version, since } => changelog(ctx, &version, since.as_deref())?,
+        Commands::Info { version } => info(ctx, &version)?,
+        Commands::Du { json } => du(ctx, json)?,
+        Commands::Outdated { json } => outdated(ctx, json)?,
+        Commands::Alias {
+            name,
+            version,
+            remove,
+        } => alias(ctx, name.as_deref(), version.as_deref(), remove)?,
+        Commands::Pin { version } => pin(ctx, &version)?,
+        Commands::Unpin { version } => unpin(ctx, &version)?,
+        Commands::Default { version, unset } => default_version(ctx, version.as_deref(), unset)?,
+        Commands::Restore => restore(ctx)?,
+        Commands::Current {
+            is: Some(version),
+            verbose,
+            ..
+        } => {
+            // only the exit status is of interest, unless asked to be verbose
+            match is_current(ctx, &version) {
+                Ok(true) => {
+                    if verbose {
+                        ctx.reporter
+                            .output(&format!("Version {} is active", version));
+                    }
+                }
+                Ok(false) => {
+                    if verbose {
+                        ctx.reporter
+                            .output(&format!("Version {} is not active", version));
+                    }
+
+                    process::exit(1);
+                }
+                Err(error) => {
+                    ctx.reporter.error(&error.to_string());
+
+                    process::exit(2);
+                }
+            }
+        }
+        Commands::Current {
+            is: None,
+            json: true,
+            ..
+        } => current_json(ctx)?,
+        Commands::Current { is: None, .. } => {
+            // get the current version
+            let version = current(ctx)?;
+
+            ctx.reporter
+                .output(&format!("Current version: {}", version));
+        }
+        Commands::Purge {
+            all: true,
+            keep_active,
+            ..
+        } => purge_all(ctx, keep_active)?,
+        Commands::Purge { unused: true, .. } => purge_all(ctx, true)?,
+        Commands::Purge {
+            older_than: Some(duration),
+            ..
+        } => purge_older_than(ctx, &duration)?,
+        Commands::Purge {
+            versions,
+            no_interactive,
+            ..
+        } => {
+            if versions.is_empty() {
+                if no_interactive {
+                    return Err("No version given to purge".into());
+                }
+
+                purge(ctx, &[pick_version(ctx, "Purge:", false)?])?;
+            } else {
+                purge(ctx, &versions)?;
+            }
+        }
+        Commands::Update {
+            check,
+            notify,
+            force,
+        } => update(ctx, check, notify, force)?,
+        Commands::PruneNightlies { keep } => prune_nightlies(ctx, keep)?,
+        Commands::Lock { reason } => lock(ctx, reason.as_deref())?,
+        Commands::Unlock => {
+            if state::lock()?.is_none() {
+                ctx.reporter.info("The install is not locked");
+            } else {
+                state::set_lock(None)?;
+                ctx.reporter.success("Unlocked the install");
+            }
+        }
+        Commands::Rollback { to } => rollback(ctx, to)?,
+        Commands::History => show_history(ctx)?,
+        Commands::Clean => clean(ctx)?,
+        Commands::Uninstall => uninstall(ctx)?,
+        Commands::Doctor => doctor::doctor(ctx)?,
+        Commands::Repair => repair(ctx)?,
+        Commands::Verify { fix } => verify::verify(ctx, fix)?,
+        Commands::Migrate { paths, remove } => migrate::migrate(ctx, &paths, remove)?,
+        Commands::Channel {
+            action: ChannelAction::Add { version },
+        } => channel::add(ctx, &version)?,
+        Commands::Channel {
+            action: ChannelAction::Refresh,
+        } => channel::refresh(ctx)?,
+        Commands::Channel {
+            action: ChannelAction::Remove { version },
+        } => channel::remove(ctx, &version)?,
+        Commands::Channel {
+            action: ChannelAction::List,
+        } => channel::list(ctx)?,
+        Commands::Shim {
+            action: ShimAction::Install,
+        } => shim::install(ctx)?,
+        Commands::Shim {
+            action: ShimAction::Uninstall,
+        } => shim::uninstall(ctx)?,
+        Commands::Shim {
+            action: ShimAction::Exec { args },
+        } => shim::exec(ctx, &args)?,
+        Commands::Profile {
+            action:
+                ProfileAction::Add {
+                    name,
+                    version,
+                    appname,
+                },
+        } => profile::add(ctx, &name, &version, appname.as_deref())?,
+        Commands::Profile {
+            action: ProfileAction::Remove { name },
+        } => profile::remove(ctx, &name)?,
+        Commands::Profile {
+            action: ProfileAction::List,
+        } => profile::list(ctx)?,
+        Commands::Run { version, args } => run_version(ctx, &version, &args)?,
+        Commands::Try { version, args } => try_version(ctx, &version, &args)?,
+        Commands::Exec { version, command } => exec(ctx, &version, &command)?,
+        Commands::Which { version } => match which(ctx, version.as_deref())? {
+            Some(binary) => ctx.reporter.output(&binary.display().to_string()),
+            None => {
+                ctx.reporter.error(&match version {
+                    Some(version) => format!("Version {} is not installed", version),
+                    None => "No version installed".to_string(),
+                });
+
+                process::exit(NOT_INSTALLED);
+            }
+        },
+        Commands::Url { version } => {
+            for url in asset_urls(ctx, &resolve_version(ctx, &version)?)? {
+                ctx.reporter.output(&url);
+            }
+        }
+        Commands::Bisect {
+            versions,
+            good,
+            bad,
+            command,
+        } => {
+            // the versions not given as an option are taken from the arguments in order
+            let mut versions = versions.into_iter();
+            let good = good.or_else(|| versions.next());
+            let bad = bad.or_else(|| versions.next());
+
+            match (good, bad) {
+                (Some(good), Some(bad)) if versions.next().is_none() => {
+                    bisect::bisect(ctx, &good, &bad, &command)?
+                }
+                (Some(_), Some(_)) => return Err("Too many versions given to bisect".into()),
+                _ => return Err("Bisect needs a good and a bad version".into()),
+            }
+        }
+        Commands::Benchmark { versions, runs } => {
+            benchmark::benchmark(ctx, &versions, runs as usize)?
+        }
+        Commands::Sync { file, prune } => sync::sync(ctx, file.as_deref(), prune)?,
+        Commands::Export { file } => {
+            let content = sync::export(ctx)?;
+
+            match file {
+                Some(file) => {
+                    if fs::write(&file, content).is_err() {
+                        return Err(format!("Failed to write {}", file.display()).into());
+                    }
+
+                    ctx.reporter
+                        .success(&format!("Exported to {}", file.display()));
+                }
+                None => ctx.reporter.output(content.trim_end()),
+            }
+        }
+        Commands::Import { file } => sync::sync(ctx, Some(&file), false)?,
+        Commands::Bundle {
+            action: BundleAction::Export { file, versions },
+        } => bundle::export(ctx, &file, &versions)?,
+        Commands::Bundle {
+            action: BundleAction::Import { file },
+        } => bundle::import(ctx, &file)?,
+        Commands::Watch { interval, switch } => {
+            let interval = timestamp::parse_duration(&interval)?;
+            if interval == 0 {
+                return Err("The interval has to be longer than 0 seconds".into());
+            }
+
+            watch::watch(ctx, interval, switch)?
+        }
+        Commands::Systemd {
+            action:
+                SystemdAction::Install {
+                    schedule,
+                    nightly,
+                    switch,
+                    notify,
+                },
+        } => systemd::install(ctx, &schedule, nightly, switch, notify)?,
+        Commands::Systemd {
+            action: SystemdAction::Uninstall,
+        } => systemd::uninstall(ctx)?,
+        Commands::Matrix { versions, command } => matrix::matrix(ctx, &versions, &command)?,
+        Commands::Hook { shell } => {
+            let exe = env::current_exe()?;
+
+            ctx.reporter
+                .output(shell::hook(shell, &exe.display().to_string())?.trim_end());
+        }
+        Commands::Completions { shell } => {
+            ctx.reporter
+                .output(completions::completions(shell, Args::command()).trim_end());
+        }
+        Commands::Tui => tui::tui(ctx)?,
+        Commands::Manpage { dir } => {
+            let pages = manpage::write(Args::command(), &dir)?;
+
+            ctx.reporter
+                .success(&format!("Wrote {} man pages to {}", pages, dir.display()));
+        }
+        Commands::CompleteVersions => complete_versions(ctx)?,
+        Commands::Env { shell } => {
+            let bin = paths::link_dir()?.join("bin");
+
+            ctx.reporter.output(
+                shell::env(
+                    shell.unwrap_or_else(Shell::detect),
+                    &bin.display().to_string(),
+                )
+                .trim_end(),
+            );
+        }
+        Commands::SelfUpdate { check } => self_update::self_update(ctx, check)?,
+    }
+
+    Ok(())
+}
+
+// Download the specified version of nvim and store it in our cache
+fn download(ctx: &Context, version: &str) -> Result<Box<Path>, Box<dyn std::error::Error>> {
+    // resolve the version into the upstream tag
+    let version = &resolve_version(ctx, version)?;
+
+    // get the file path
+    let path = paths::archive(version)?;
+
+    // nightly is replaced upstream every day, so find out which upload is the newest
+    // forks publishing a nightly replace it the same way
+    let revision = match version::split_repo(version).1 {
+        "nightly" => upstream_revision(ctx, version),
+        _ => None,
+    };
+
+    // if the cache dir contains the version, there is no point in downloading it again
+    // unless a newer upload of the same tag is available
+    if path.exists() {
+        let stale = match &revision {
+            Some(revision) => fs::read_to_string(paths::revision(version)?)
+                .map(|cached| cached.trim() != revision)
+                .unwrap_or(true),
+            None => false,
+        };
+
+        if !stale {
+            ctx.reporter
+                .info(&format!("Version {} already downloaded", version));
+
+            return Ok(path);
+        }
+
+        ctx.reporter.info(&format!(
+            "A newer build of version {} is available",
+            version
+        ));
+    }
+
+    // snapshots only exist in the cache, there is nothing to download them from
+    if version::is_snapshot(version) {
+        return Err(format!(
+            "Version {} is not cached, nightly snapshots cannot be downloaded again",
+            version
+        )
+        .into());
+    }
+
+    // nothing can be downloaded offline
+    if ctx.offline.get() {
+        return Err(format!(
+            "Version {} is not cached and cannot be downloaded offline",
+            version
+        )
+        .into());
+    }
+
+    // keep the nightly build being replaced, so that night can be returned to
+    let snapshot = match version.as_str() {
+        "nightly" if path.exists() => Some(snapshot_nightly(ctx, &path)?),
+        _ => None,
+    };
+
+    // put the kept build back when the newer one could not be stored
+    let restore = || {
+        if let Some(snapshot) = &snapshot {
+            if let Ok(kept) = paths::archive(snapshot) {
+                let _ = fs::rename(kept, &path);
+                let _ = state::forget_download(snapshot);
+            }
+        }
+    };
+
+    // try the mirrors in order until one of them serves the file
+    // a mirror serving something other than the archive, e.g. an error page, is skipped like a failing one
+    let urls = asset_urls(ctx, version)?;
+    let mut served = None;
+    for url in &urls {
+        ctx.reporter
+            .info(&format!("Pulling version {} of nvim from {}", version, url));
+
+        let download = || fetch(ctx, url, &path).and_then(|()| check_archive(&path));
+        let verify = || checksum::verify(ctx, version, url, &path);
+
+        // a corrupted transfer rarely repeats, so a mismatching checksum gets one more download
+        let result = download()
+            .and_then(|()| {
+                verify().or_else(|error| {
+                    ctx.reporter.warn(&error.to_string());
+
+                    download().and_then(|()| verify())
+                })
+            })
+            .and_then(|()| signature::verify(ctx, version, url, &path))
+            .and_then(|()| attestation::verify(ctx, version, &path));
+        if result.is_err() {
+            let _ = fs::remove_file(&path);
+        }
+
+        match result {
+            Ok(()) => {
+                served = Some(url.clone());
+
+                break;
+            }
+            Err(error) => {
+                ctx.reporter.warn(&error.to_string());
+            }
+        }
+    }
+
+    // did any of the mirrors work
+    let url = match served {
+        Some(url) => url,
+        None => {
+            restore();
+            http::detect_offline(ctx);
+
+            return Err(match urls.len() {
+                1 => "Failed to download version".to_string(),
+                count => format!(
+                    "Failed to download version from any of the {} mirrors",
+                    count
+                ),
+            }
+            .into());
+        }
+    };
+
+    // remember when the version was downloaded, for purge --older-than
+    if state::record_download(version, timestamp::seconds(SystemTime::now())).is_err() {
+        ctx.reporter.warn(&format!(
+            "Failed to record the download time of version {}",
+            version
+        ));
+    }
+
+    // remember which upload was downloaded, so the next download can tell whether it is stale
+    if let Some(revision) = revision {
+        if fs::write(paths::revision(version)?, revision).is_err() {
+            ctx.reporter.warn(&format!(
+                "Failed to record the build of version {}",
+                version
+            ));
+        }
+    }
+
+    ctx.reporter.success(&format!(
+        "Downloaded version {} of nvim from {}",
+        version, url
+    ));
+
+    // keep the cache bounded
+    retain(ctx, version)?;
+
+    Ok(path)
+}
+
+// get the revision of the upstream asset of a version, see github::Asset::revision
+// failing to reach github is not fatal, the cached archive is used instead
+fn upstream_revision(ctx: &Context, version: &str) -> Option<String> {
+    // the cached archive is all there is offline
+    if ctx.offline.get() {
+        return None;
+    }
+
+    // get the release and the name of the asset for this machine
+    let revision = asset_name(ctx, version).and_then(|name| {
+        let release = github::release(ctx, version)?;
+
+        Ok(release
+            .assets
+            .into_iter()
+            .find(|asset| asset.name == name)
+            .and_then(|asset| asset.revision()))
+    });
+
+    match revision {
+        Ok(revision) => revision,
+        Err(error) => {
+            ctx.reporter.warn(&format!(
+                "Could not check for a newer build of version {}: {}",
+                version, error
+            ));
+
+            None
+        }
+    }
+}
+
+// move the cached nightly build aside as a snapshot named after the day it was downloaded
+// a snapshot of the same day is replaced, returns the name of the snapshot
+fn snapshot_nightly(ctx: &Context, archive: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let downloaded =
+        downloaded_at("nightly").unwrap_or_else(|| timestamp::seconds(SystemTime::now()));
+    let snapshot = format!("nightly-{}", &timestamp::format(downloaded)[..10]);
+
+    fs::rename(archive, paths::archive(&snapshot)?)?;
+    state::record_download(&snapshot, downloaded)?;
+
+    ctx.reporter
+        .info(&format!("Kept the previous nightly build as {}", snapshot));
+
+    Ok(snapshot)
+}
+
+// remove the oldest nightly snapshots beyond the number to keep
+// pinned snapshots and the active version are left alone and do not count towards the number
+fn prune_nightlies(ctx: &Context, keep: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let active = active_version(ctx);
+
+    // the names sort by date, newest first
+    let mut snapshots: Vec<String> = unpinned_versions()?
+        .into_iter()
+        .filter(|version| version::is_snapshot(version) && active.as_ref() != Some(version))
+        .collect();
+    snapshots.sort_by(|a, b| b.cmp(a));
+
+    if snapshots.len() <= keep {
+        ctx.reporter.info(&format!(
+            "There are {} nightly snapshots, nothing to prune",
+            snapshots.len()
+        ));
+
+        return Ok(());
+    }
+
+    let mut reclaimed = 0;
+    for snapshot in &snapshots[keep..] {
+        reclaimed += purge_version(ctx, snapshot)?;
+    }
+
+    report_reclaimed(ctx, reclaimed);
+
+    Ok(())
+}
+
+// download the url into the file
+fn fetch(ctx: &Context, url: &str, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let client = http::client(ctx)?;
+
+    // the file is written next to its path until it is complete, with what identifies the upload
+    // beside it, so a download that broke off is resumed from where it stopped, even in a later run
+    let with_suffix = |suffix: &str| {
+        let mut name = path.as_os_str().to_owned();
+        name.push(suffix);
+        PathBuf::from(name)
+    };
+    let partial = with_suffix(".partial");
+    let validator = with_suffix(".partial.validator");
+
+    // a large file is split over several connections, unless a part of it is there to resume
+    if !partial.exists() {
+        if let Some(size) = parallel::size(ctx, &client, url) {
+            match parallel::fetch(ctx, &client, url, &partial, size) {
+                Ok(()) => {
+                    fs::rename(&partial, path)?;
+                    let _ = fs::remove_file(&validator);
+
+                    return Ok(());
+                }
+                Err(error) => {
+                    let _ = fs::remove_file(&partial);
+
+                    ctx.reporter.warn(&format!(
+                        "Failed to download {} in parts: {}, using a single connection",
+                        url, error
+                    ));
+                }
+            }
+        }
+    }
+
+    // whether what was downloaded so far is worth keeping when the download fails
+    let resumable = Cell::new(false);
+
+    let attempt = || -> Result<(), http::Failure> {
+        let stored = || http::Failure::permanent("the version could not be stored");
+
+        // only resume an upload that can be recognised, if-range makes the server send all of it
+        // when it changed since
+        let offset = fs::metadata(&partial)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        let known = fs::read_to_string(&validator).ok();
+        let mut request = http::authorize(ctx, client.get(url), url);
+        if let (true, Some(known)) = (offset > 0, &known) {
+            request = request
+                .header("Range", format!("bytes={}-", offset))
+                .header("If-Range", known.trim());
+        }
+
+        // attempt to download the file
+        let mut response = http::send(ctx, request)?;
+
+        // was it successful
+        let resumed = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+        if response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+            // what was kept does not fit the upload, start over
+            let _ = fs::remove_file(&partial);
+            let _ = fs::remove_file(&validator);
+
+            return Err(http::Failure::retryable(
+                "the download could not be resumed",
+            ));
+        }
+        if !response.status().is_success() {
+            return Err(http::Failure::permanent(format!(
+                "the server responded with {}",
+                response.status()
+            )));
+        }
+
+        // the size the file has once complete, from the total of the range or the length of the body
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(String::from)
+        };
+        let expected = match resumed {
+            true => header("Content-Range")
+                .and_then(|range| range.rsplit_once('/').map(|(_, total)| total.to_string()))
+                .and_then(|total| total.parse::<u64>().ok()),
+            false => response.content_length(),
+        };
+
+        // a full response replaces whatever was downloaded before
+        let file = match resumed {
+            true => OpenOptions::new().append(true).open(&partial),
+            false => {
+                let identity = header("ETag").or_else(|| header("Last-Modified"));
+                match identity {
+                    Some(identity) => fs::write(&validator, identity).map_err(|_| stored())?,
+                    None => {
+                        let _ = fs::remove_file(&validator);
+                    }
+                }
+
+                File::create(&partial)
+            }
+        };
+        let mut file = BufWriter::with_capacity(BUFFER_SIZE, file.map_err(|_| stored())?);
+        resumable.set(true);
+
+        if resumed {
+            ctx.reporter
+                .info(&format!("Resuming the download at {}", format_size(offset)));
+        }
+
+        // stream the body to the file through a fixed size buffer,
+        // so memory use does not grow with the size of the archive
+        // reading fails when the connection drops, which may pass, writing when the disk is full, which does not
+        let mut buffer = vec![0; BUFFER_SIZE];
+        let mut failure = None;
+        let mut progress = Progress::new(ctx, if resumed { offset } else { 0 }, expected);
+        let throttle = Throttle::new(ctx.limit_rate);
+        loop {
+            let read = match response.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(error) if http::timed_out(&error) => {
+                    failure = Some(http::Failure::retryable(format!(
+                        "the download stalled for {}s",
+                        ctx.read_timeout.as_secs()
+                    )));
+                    break;
+                }
+                Err(_) => {
+                    failure = Some(http::Failure::retryable("the download was interrupted"));
+                    break;
+                }
+            };
+
+            file.write_all(&buffer[..read]).map_err(|_| stored())?;
+            progress.advance(read as u64);
+            throttle.wait(read as u64);
+        }
+        drop(progress);
+
+        // what arrived is kept for the next attempt
+        file.flush().map_err(|_| stored())?;
+        if let Some(failure) = failure {
+            return Err(failure);
+        }
+
+        let size = fs::metadata(&partial).map_err(|_| stored())?.len();
+        if expected.is_some_and(|expected| expected != size) {
+            // a file longer than the server says cannot be resumed
+            let _ = fs::remove_file(&partial);
+
+            return Err(http::Failure::retryable("the download ended early"));
+        }
+
+        fs::rename(&partial, path).map_err(|_| stored())?;
+        let _ = fs::remove_file(&validator);
+
+        Ok(())
+    };
+
+    match http::retry(ctx, &format!("Failed to download {}", url), attempt) {
+        Ok(()) => Ok(()),
+        Err(error) => {
+            // only a download that broke off is kept to resume
+            if !resumable.get() || !partial.exists() || !validator.exists() {
+                let _ = fs::remove_file(&partial);
+                let _ = fs::remove_file(&validator);
+            }
+
+            Err(error.into())
+        }
+    }
+}
+
+// fail when the install is locked at a version other than the one given
+fn check_lock(version: &str) -> Result<(), Box<dyn std::error::Error>> {
+    match state::lock()? {
+        Some(lock) if lock.version != version => Err(format!(
+            "The install is locked at version {}{}, run unlock or pass --force to switch anyway",
+            lock.version,
+            lock.reason
+                .map(|reason| format!(" ({})", reason))
+                .unwrap_or_default()
+        )
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+// lock the install at the active version
+fn lock(ctx: &Context, reason: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let version = match active_version(ctx) {
+        Some(version) => version,
+        None => return Err("No version installed".into()),
+    };
+
+    // the reason is stored as a single line
+    let reason = reason
+        .map(|reason| reason.split_whitespace().collect::<Vec<&str>>().join(" "))
+        .filter(|reason| !reason.is_empty());
+
+    state::set_lock(Some(&state::Lock {
+        version: version.clone(),
+        reason,
+    }))?;
+
+    ctx.reporter
+        .success(&format!("Locked the install at version {}", version));
+
+    Ok(())
+}
+
+// Switch to the specified version of nvim
+fn switch(ctx: &Context, version: &str) -> Result<(), Box<dyn std::error::Error>> {
+    switch_version(ctx, version, false)
+}
+
+// switch to a version, with force it is installed again even when it is already in use
+fn switch_version(
+    ctx: &Context,
+    version: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // resolve the version into the upstream tag
+    let version = &resolve_version(ctx, version)?;
+
+    // a locked install only changes when forced
+    if !force {
+        check_lock(version)?;
+    }
+
+    // is the current version the same as the one we are switching to
+    // the manifest tells a fork apart from the same version of neovim, which nvim itself cannot
+    if !force && active_version(ctx).is_some_and(|active| active == *version) {
+        ctx.reporter
+            .success(&format!("Already using version {}", version));
+
+        return Ok(());
+    }
+
+    ctx.reporter
+        .info(&format!("Switching to version {}", version));
+
+    // remember what was active, so the switch can be rolled back
+    let previous = active_version(ctx);
+
+    // install the version and record the outcome, whether it succeeded or not
+    let result = install(ctx, version);
+    if let Err(error) = history::record(previous.as_deref(), version, result.is_ok()) {
+        ctx.reporter.warn(&error.to_string());
+    }
+    result?;
+
+    if let Some(previous) = previous.filter(|previous| previous != version) {
+        state::set_previous(&previous)?;
+    }
+
+    ctx.reporter
+        .success(&format!("Switched to version {}", version));
+
+    // keep the cache bounded
+    retain(ctx, version)?;
+
+    // make sure the new version actually runs on this system
+    if let Err(error) = current(ctx) {
+        ctx.reporter.warn(&error.to_string());
+    }
+
+    Ok(())
+}
+
+// download and extract the version and link it into the link dir
+fn install(ctx: &Context, version: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // get the path
+    let path = paths::archive(version)?;
+
+    // get the output dir, before downloading anything it may turn out not to be replaceable
+    let dir = paths::output_dir(ctx)?;
+    paths::check_output_dir(ctx, &dir)?;
+
+    if !path.exists() && stream::available(ctx, version) {
+        // extract the version while it downloads
+        stream::install(ctx, version, &dir)?;
+    } else {
+        // download the version if it is not already downloaded
+        // nightly is always checked, the cached archive may be outdated
+        if !path.exists() || version == "nightly" {
+            // download the version
+            if download(ctx, version).is_err() {
+                return Err(format!("Failed to download version {}", version).into());
+            }
+        }
+
+        // make sure the archive can be extracted before removing the current version
+        check_archive(&path)?;
+
+        // remove the current version
+        if fs::remove_dir_all(&dir).is_err() {
+            return Err("Failed to remove current version".into());
+        }
+
+        // extract the version
+        if extract(&path, &dir).is_err() {
+            return Err("Failed to extract version".into());
+        }
+    }
+    paths::mark_output_dir(&dir)?;
+
+    // determine the symlinks for the version
+    let mut links = links::plan(&dir, &paths::link_dir()?, &ctx.bin_aliases)?;
+
+    // the shim stands in for the nvim symlink and finds the installed version by itself
+    if shim::is_installed() {
+        let shim = shim::path()?;
+        links.retain(|link| link.link != shim);
+    }
+
+    // create the symlinks
+    for link in &links {
+        links::create(link)?;
+    }
+
+    // remove the symlinks of the previous version that are no longer needed, e.g. old aliases
+    if let Some(previous) = Manifest::load()? {
+        for link in previous.links {
+            if !links.iter().any(|new| new.link == link.link) {
+                links::remove(&link)?;
+            }
+        }
+    }
+
+    // remember the symlinks so they can be repaired and cleaned up
+    Manifest {
+        version: version.to_string(),
+        links,
+    }
+    .save()
+}
+
+// the exit status of update --check when a newer stable release is available
+static UPDATE_AVAILABLE: i32 = 10;
+
+// switch to the newest stable release
+fn update(
+    ctx: &Context,
+    check: bool,
+    notify: bool,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // get the newest stable release
+    let latest = resolve_version(ctx, "stable")?;
+
+    // is the active version already at least as new
+    let active = active_version(ctx);
+    let up_to_date = active.as_ref().is_some_and(|active| {
+        *active == latest
+            || version::number(active)
+                .zip(version::number(&latest))
+                .is_some_and(|(active, latest)| active >= latest)
+    });
+
+    if check {
+        if up_to_date {
+            ctx.reporter
+                .output(&format!("Version {} is up to date", latest));
+
+            return Ok(());
+        }
+
+        ctx.reporter.output(&format!(
+            "Version {} is available (using {})",
+            latest,
+            active.as_deref().unwrap_or("None")
+        ));
+
+        // a scheduled check would otherwise raise the same notification every time it runs
+        if notify && state::notified()?.as_ref() != Some(&latest) {
+            match notify::send(
+                &format!("Neovim {} is available", latest),
+                "Run nvim_switcher update to switch to it",
+            ) {
+                Ok(()) => state::set_notified(&latest)?,
+                Err(error) => ctx.reporter.warn(&error.to_string()),
+            }
+        }
+
+        process::exit(UPDATE_AVAILABLE);
+    }
+
+    if up_to_date {
+        ctx.reporter
+            .success(&format!("Version {} is up to date", latest));
+
+        return Ok(());
+    }
+
+    switch_version(ctx, &latest, force)
+}
+
+// run a version of nvim with the arguments, replacing this process
+fn run_version(
+    ctx: &Context,
+    version: &str,
+    args: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    // a profile runs its version with its own config
+    let profile = state::profile(version)?;
+    let dir = match &profile {
+        Some(profile) => extracted(ctx, &profile.version)?,
+        None => extracted(ctx, version)?,
+    };
+
+    let mut command = Command::new(dir.join("bin/nvim"));
+    command.args(args);
+    if let Some(profile) = &profile {
+        command.env("NVIM_APPNAME", &profile.appname);
+    }
+
+    // exec only returns when nvim could not be started
+    let error = command.exec();
+
+    Err(format!("Failed to run nvim: {}", error).into())
+}
+
+// run a version of nvim with a config, data, state and cache of its own that are removed afterwards
+// so neither the config nor the plugins of the user are loaded
+fn try_version(
+    ctx: &Context,
+    version: &str,
+    args: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = extracted(ctx, version)?;
+
+    let sandbox = env::temp_dir().join(format!("nvim_switcher-try-{}", process::id()));
+    paths::ensure_dir(&sandbox)?;
+
+    ctx.reporter.info(&format!(
+        "Running version {} with an empty config in {}",
+        version,
+        sandbox.display()
+    ));
+
+    // the appname keeps nvim to the name it always uses inside the sandbox
+    let status = Command::new(dir.join("bin/nvim"))
+        .args(args)
+        .env("NVIM_APPNAME", "nvim")
+        .env("XDG_CONFIG_HOME", sandbox.join("config"))
+        .env("XDG_DATA_HOME", sandbox.join("data"))
+        .env("XDG_STATE_HOME", sandbox.join("state"))
+        .env("XDG_CACHE_HOME", sandbox.join("cache"))
+        .status();
+
+    let _ = fs::remove_dir_all(&sandbox);
+
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        // pass on how nvim exited, e.g. for a crash
+        Ok(status) => process::exit(status.code().unwrap_or(1)),
+        Err(error) => Err(format!("Failed to run nvim: {}", error).into()),
+    }
+}
+
+// run a command with the version first on the PATH, replacing this process
+// only the environment of the command changes, the active version stays as it is
+fn exec(
+    ctx: &Context,
+    version: &str,
+    command: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = extracted(ctx, version)?;
+
+    // exec only returns when the command could not be started
+    let error = version_command(&dir, command)?.exec();
+
+    Err(format!("Failed to run {}: {}", command[0], error).into())
+}
+
+// build the command with the version extracted to the directory first on the PATH
+fn version_command(dir: &Path, command: &[String]) -> Result<Command, Box<dyn std::error::Error>> {
+    // put the binary of the version in front of everything else
+    let mut path = vec![dir.join("bin")];
+    path.extend(env::split_paths(&env::var_os("PATH").unwrap_or_default()));
+
+    let mut child = Command::new(&command[0]);
+    child
+        .args(&command[1..])
+        .env("PATH", env::join_paths(path)?)
+        .env("VIMRUNTIME", dir.join("share/nvim/runtime"));
+
+    Ok(child)
+}
+
+// get the directory of a version extracted next to the active one, extracting it when needed
+// this leaves the symlinks and the active version alone
+fn extracted(ctx: &Context, version: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let version = &resolve_version(ctx, version)?;
+    let path = paths::archive(version)?;
+
+    // download the version if it is not already downloaded, nightly may be outdated
+    if !path.exists() || version == "nightly" {
+        download(ctx, version)?;
+    }
+
+    // the archive is newer than the extracted files when it was downloaded again
+    let dir = paths::version_dir(version)?.to_path_buf();
+    let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified());
+    let current = match (modified(&path), modified(&dir)) {
+        (Ok(archive), Ok(extracted)) => archive <= extracted && dir.join("bin/nvim").exists(),
+        _ => false,
+    };
+
+    if !current {
+        ctx.reporter
+            .info(&format!("Extracting version {}", version));
+
+        // extract next to the directory first, so an interrupted extraction is never used
+        let partial = dir.with_extension("partial");
+        let _ = fs::remove_dir_all(&partial);
+        paths::ensure_dir(&partial)?;
+        if let Err(error) = extract(&path, &partial) {
+            let _ = fs::remove_dir_all(&partial);
+
+            return Err(format!("Failed to extract version {}: {}", version, error).into());
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+        fs::rename(&partial, &dir)?;
+    }
+
+    Ok(dir)
+}
+
+// switch back to the version that was active before the last switch
+// or to the version a given entry of the history switched to
+fn rollback(ctx: &Context, to: Option<usize>) -> Result<(), Box<dyn std::error::Error>> {
+    let version = match to {
+        Some(number) => {
+            // entries are numbered from 1 as printed by the history command
+            let entries = history::entries()?;
+            let entry = match number.checked_sub(1).and_then(|index| entries.get(index)) {
+                Some(entry) => entry,
+                None => {
+                    return Err(format!("There is no history entry {}", number).into());
+                }
+            };
+
+            // a failed switch never made its version active
+            if !entry.ok {
+                return Err(format!("History entry {} is a failed switch", number).into());
+            }
+
+            entry.to.clone()
+        }
+        None => match state::previous()? {
+            Some(previous) => previous,
+            None => {
+                return Err("No previous version to roll back to".into());
+            }
+        },
+    };
+
+    switch(ctx, &version)
+}
+
+// print the switches made so far, oldest first
+fn show_history(ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    let entries = history::entries()?;
+
+    if entries.is_empty() {
+        ctx.reporter.info("No switches recorded yet");
+
+        return Ok(());
+    }
+
+    for (index, entry) in entries.iter().enumerate() {
+        ctx.reporter.output(&format!(
+            "{:>4}  {}  {} -> {}{}",
+            index + 1,
+            timestamp::format(entry.time),
+            entry.from.as_deref().unwrap_or("None"),
+            entry.to,
+            if entry.ok { "" } else { " (failed)" }
+        ));
+    }
+
+    Ok(())
+}
+
+// recreate the missing or broken symlinks of the active version
+fn repair(ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    // use the symlinks recorded at switch time, or determine them for installs older than the manifest
+    let links = match Manifest::load()? {
+        Some(manifest) => manifest.links,
+        None => links::plan(
+            &paths::output_dir(ctx)?,
+            &paths::link_dir()?,
+            &ctx.bin_aliases,
+        )?,
+    };
+
+    // is there anything to repair
+    if links.is_empty() {
+        ctx.reporter.info("No version installed");
+
+        return Ok(());
+    }
+
+    // recreate the symlinks that are not intact
+    let mut repaired = 0;
+    for link in links.iter().filter(|link| !links::is_intact(link)) {
+        // the extracted version itself is missing, a symlink cannot fix that
+        if !link.target.exists() {
+            return Err(format!(
+                "{} is missing, run verify --fix to extract the version again",
+                link.target.display()
+            )
+            .into());
+        }
+
+        links::create(link)?;
+
+        ctx.reporter
+            .info(&format!("Repaired {}", link.link.display()));
+
+        repaired += 1;
+    }
+
+    ctx.reporter.success(&format!(
+        "Repaired {} of {} symlinks",
+        repaired,
+        links.len()
+    ));
+
+    Ok(())
+}
+
+// remove the broken symlinks that point into the install or cache directory
+fn clean(ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    // the links whose target is gone
+    let dangling: Vec<_> = links::find(&paths::link_dir()?, &owned_dirs(ctx)?)?
+        .into_iter()
+        .filter(|link| !link.target.exists())
+        .collect();
+
+    for link in &dangling {
+        if fs::remove_file(&link.link).is_err() {
+            return Err(format!("Failed to remove {}", link.link.display()).into());
+        }
+
+        ctx.reporter
+            .info(&format!("Removed {}", link.link.display()));
+    }
+
+    ctx.reporter
+        .success(&format!("Removed {} broken symlinks", dangling.len()));
+
+    Ok(())
+}
+
+// remove everything nvim_switcher created, except for the config file
+fn uninstall(ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    if !prompt::confirm(
+        ctx,
+        "Remove every installed and downloaded version of nvim and all nvim_switcher state?",
+    )? {
+        ctx.reporter.info("Nothing was removed");
+
+        return Ok(());
+    }
+
+    // an install dir holding anything else is left alone, before any of it is removed
+    let output = paths::output_dir(ctx)?;
+    paths::check_output_dir(ctx, &output)?;
+
+    // remove the symlinks first, they point into the directories removed below
+    let mut removed = 0;
+    for link in links::find(&paths::link_dir()?, &owned_dirs(ctx)?)? {
+        if fs::remove_file(&link.link).is_err() {
+            return Err(format!("Failed to remove {}", link.link.display()).into());
+        }
+
+        removed += 1;
+    }
+
+    ctx.reporter.info(&format!("Removed {} symlinks", removed));
+
+    if shim::is_installed() {
+        fs::remove_file(shim::path()?)?;
+        ctx.reporter.info("Removed the shim");
+    }
+
+    // remove the extracted version, the cache with the downloads and the state
+    for dir in [output, paths::cache_dir()?, paths::data_dir()?] {
+        if dir.exists() && fs::remove_dir_all(&dir).is_err() {
+            return Err(format!("Failed to remove {}", dir.display()).into());
+        }
+
+        ctx.reporter.info(&format!("Removed {}", dir.display()));
+    }
+
+    ctx.reporter.success(&format!(
+        "Uninstalled, the config in {} was left untouched",
+        paths::config_dir()?.display()
+    ));
+
+    Ok(())
+}
+
+// the directories the symlinks point into, links into them are ours to remove
+fn owned_dirs(ctx: &Context) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    Ok(vec![
+        paths::output_dir(ctx)?.to_path_buf(),
+        paths::cache_dir()?.to_path_buf(),
+    ])
+}
+
+// the size of the buffers used while downloading and extracting
+// downloads and extractions stream through buffers of this size, the file, the decoder and a single
+// entry never have to fit in memory, so memory use stays around a few of these buffers plus the
+// window of the gzip decoder (32 KiB), whatever the size of the archive
+static BUFFER_SIZE: usize = 64 * 1024;
+
+// the first bytes of every gzip file
+static GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// make sure the file is a gzip archive and not e.g. an html page served by a proxy
+fn check_archive(file: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    // read the first bytes of the file
+    let mut magic = [0; 2];
+    if File::open(file)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_err()
+        || magic != GZIP_MAGIC
+    {
+        return Err(
+            "Downloaded file is not a valid gzip archive (got HTML?) — check your proxy/URL".into(),
+        );
+    }
+
+    Ok(())
+}
+
+// open the gzip compressed archive for reading
+fn open_archive(
+    file: &Path,
+) -> Result<Archive<GzDecoder<BufReader<File>>>, Box<dyn std::error::Error>> {
+    // refuse anything that is not a gzip archive
+    check_archive(file)?;
+
+    // open the file
+    let file = File::open(file)?;
+
+    // decompress the file
+    // the decoder and the archive both stream, so only the buffer is held in memory
+    let decompressed = GzDecoder::new(BufReader::with_capacity(BUFFER_SIZE, file));
+
+    // create the archive to read the content
+    Ok(Archive::new(decompressed))
+}
+
+// list the content of the cached archive of a version without extracting it
+fn inspect(
+    ctx: &Context,
+    version: &str,
+    download_missing: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // resolve the version into the upstream tag
+    let version = &resolve_version(ctx, version)?;
+
+    // get the path
+    let mut path = paths::archive(version)?;
+
+    // download the version when asked to
+    if !path.exists() {
+        if !download_missing {
+            return Err(format!(
+                "Version {} is not downloaded, pass --download to download it first",
+                version
+            )
+            .into());
+        }
+
+        path = download(ctx, version)?;
+    }
+
+    // open the archive
+    let mut archive = open_archive(&path)?;
+
+    // print every entry like tar -tv does
+    for entry in archive.entries()? {
+        let entry = entry?;
+        let header = entry.header();
+
+        // the type of the entry
+        let kind = match header.entry_type() {
+            EntryType::Directory => 'd',
+            EntryType::Symlink => 'l',
+            _ => '-',
+        };
+
+        // the permissions of the entry
+        let mode = header.mode().unwrap_or(0);
+        let permissions: String = (0..9)
+            .map(|bit| match mode & (1 << (8 - bit)) {
+                0 => '-',
+                _ => ['r', 'w', 'x'][bit % 3],
+            })
+            .collect();
+
+        // symlinks show where they point to
+        let target = match entry.link_name()? {
+            Some(target) => format!(" -> {}", target.display()),
+            None => String::new(),
+        };
+
+        ctx.reporter.output(&format!(
+            "{}{} {:>10} {}{}",
+            kind,
+            permissions,
+            entry.size(),
+            entry.path()?.display(),
+            target
+        ));
+    }
+
+    Ok(())
+}
+
+// extract the archive into the output dir, dropping the top level directory of the archive
+fn extract(file: &Path, output_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    // open the archive
+    let mut archive = open_archive(file)?;
+
+    unpack(&mut archive, output_dir)
+}
+
+// extract the entries of an archive read from anywhere, e.g. a file or a download
+fn unpack<R: Read>(
+    archive: &mut Archive<R>,
+    output_dir: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // extract the content entry by entry
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+
+        // strip the top level directory (e.g. nvim-linux64) from the path
+        let path: PathBuf = entry.path()?.components().skip(1).collect();
+
+        // skip the top level directory itself
+        if path.as_os_str().is_empty() {
+            continue;
+        }
+
+        // never write outside of the output dir
+        if path
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_)))
+        {
+            return Err(format!("Invalid path in archive: {}", path.display()).into());
+        }
+
+        // determine where the entry ends up
+        let target = output_dir.join(path);
+
+        // create the parent directory if it does not exist
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // extract the entry
+        entry.unpack(&target)?;
+    }
+
+    Ok(())
+}
+
+// remove versions from the cache
+// each argument is either a version or a pattern like v0.9.* matched against the cached versions
+fn purge(ctx: &Context, versions: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    // get the cached versions to match the patterns against
+    let cached = paths::cached_versions()?;
+
+    let mut reclaimed = 0;
+
+    for version in versions {
+        // determine which versions the argument refers to
+        let matched: Vec<String> = if is_glob(version) {
+            // patterns may leave out the v prefix just like versions
+            let pattern = if version.starts_with(|c: char| c.is_ascii_digit()) {
+                format!("v{}", version)
+            } else {
+                version.to_string()
+            };
+
+            let matched: Vec<String> = cached
+                .iter()
+                .filter(|cached| glob_match(&pattern, cached))
+                .cloned()
+                .collect();
+
+            if matched.is_empty() {
+                ctx.reporter
+                    .warn(&format!("No cached versions match {}", version));
+            }
+
+            // a pattern can match more than the user expects, a single match is what a version would be
+            if matched.len() > 1
+                && !prompt::confirm(
+                    ctx,
+                    &format!("Remove {} matching {}?", matched.join(", "), version),
+                )?
+            {
+                continue;
+            }
+
+            matched
+        } else {
+            match resolve_version(ctx, version) {
+                Ok(version) => vec![version],
+                Err(error) => {
+                    ctx.reporter.warn(&error.to_string());
+
+                    continue;
+                }
+            }
+        };
+
+        for version in matched {
+            reclaimed += purge_version(ctx, &version)?;
+        }
+    }
+
+    report_reclaimed(ctx, reclaimed);
+
+    Ok(())
+}
+
+// remove every cached version that is not pinned, optionally keeping the active one
+fn purge_all(ctx: &Context, keep_active: bool) -> Result<(), Box<dyn std::error::Error>> {
+    // determine which versions to remove
+    let active = active_version(ctx);
+    let versions: Vec<String> = unpinned_versions()?
+        .into_iter()
+        .filter(|version| !keep_active || active.as_ref() != Some(version))
+        .collect();
+
+    if versions.is_empty() {
+        ctx.reporter.info("No cached versions to remove");
+
+        return Ok(());
+    }
+
+    if !prompt::confirm(ctx, &format!("Remove {}?", versions.join(", ")))? {
+        return Ok(());
+    }
+
+    let mut reclaimed = 0;
+    for version in versions {
+        reclaimed += purge_version(ctx, &version)?;
+    }
+
+    report_reclaimed(ctx, reclaimed);
+
+    Ok(())
+}
+
+// remove the cached versions downloaded longer ago than the duration, except the active and pinned ones
+fn purge_older_than(ctx: &Context, duration: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // anything downloaded before this is removed
+    let cutoff =
+        timestamp::seconds(SystemTime::now()).saturating_sub(timestamp::parse_duration(duration)?);
+
+    let active = active_version(ctx);
+    let versions: Vec<String> = unpinned_versions()?
+        .into_iter()
+        .filter(|version| active.as_ref() != Some(version))
+        .filter(|version| downloaded_at(version).is_some_and(|downloaded| downloaded < cutoff))
+        .collect();
+
+    if versions.is_empty() {
+        ctx.reporter.info(&format!(
+            "No cached versions downloaded more than {} ago",
+            duration
+        ));
+
+        return Ok(());
+    }
+
+    let mut reclaimed = 0;
+    for version in versions {
+        reclaimed += purge_version(ctx, &version)?;
+    }
+
+    report_reclaimed(ctx, reclaimed);
+
+    Ok(())
+}
+
+// get when a cached version was downloaded in seconds since the unix epoch
+// archives downloaded before the times were recorded fall back to their modification time
+fn downloaded_at(version: &str) -> Option<u64> {
+    if let Ok(Some(seconds)) = state::downloaded_at(version) {
+        return Some(seconds);
+    }
+
+    fs::metadata(paths::archive(version).ok()?)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .map(timestamp::seconds)
+}
+
+// remove the least recently downloaded versions beyond the number to keep
+// pinned versions are left alone and do not count towards the number
+// the active version and the version just downloaded or switched to are always kept
+// and count towards the number
+fn retain(ctx: &Context, version: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let keep = match ctx.keep_versions {
+        Some(keep) => keep,
+        None => return Ok(()),
+    };
+
+    // order the versions from most to least recently downloaded, starting with the ones always kept
+    // versions downloaded at the same time are ordered from new to old
+    let active = active_version(ctx);
+    let kept = |cached: &String| cached == version || active.as_ref() == Some(cached);
+    let mut versions = unpinned_versions()?;
+    versions.sort_by(|a, b| {
+        kept(b)
+            .cmp(&kept(a))
+            .then_with(|| downloaded_at(b).cmp(&downloaded_at(a)))
+            .then_with(|| version::compare(b, a))
+    });
+
+    // is there anything to remove
+    if versions.len() <= keep {
+        return Ok(());
+    }
+
+    // the versions always kept survive even when there are more of them than to keep
+    let mut reclaimed = 0;
+    for version in versions[keep..].iter().filter(|version| !kept(version)) {
+        reclaimed += purge_version(ctx, version)?;
+    }
+
+    report_reclaimed(ctx, reclaimed);
+
+    Ok(())
+}
+
+// get the cached versions that are not pinned, the only ones removed in bulk
+fn unpinned_versions() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let pins = state::pins()?;
+
+    Ok(paths::cached_versions()?
+        .into_iter()
+        .filter(|version| !pins.contains(version))
+        .collect())
+}
+
+// tell how much disk space removing versions freed up
+fn report_reclaimed(ctx: &Context, bytes: u64) {
+    if bytes > 0 {
+        ctx.reporter
+            .success(&format!("Reclaimed {}", format_size(bytes)));
+    }
+}
+
+// download several versions one after the other and summarize how each went
+// a failure does not stop the versions after it
+fn download_all(ctx: &Context, versions: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    // a single version fails the way it always did
+    if let [version] = versions {
+        download(ctx, version)?;
+
+        return Ok(());
+    }
+
+    let mut results = Vec::new();
+    for version in versions {
+        let result = download(ctx, version).map(|path| {
+            fs::metadata(&path)
+                .map(|metadata| format_size(metadata.len()))
+                .unwrap_or_default()
+        });
+
+        results.push((version, result));
+    }
+
+    for (version, result) in &results {
+        match result {
+            Ok(size) => ctx
+                .reporter
+                .output(&format!("  {:<10} downloaded  {}", version, size)),
+            Err(error) => ctx
+                .reporter
+                .output(&format!("  {:<10} failed      {}", version, error)),
+        }
+    }
+
+    let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+    if failed > 0 {
+        return Err(format!(
+            "Failed to download {} of {} versions",
+            failed,
+            results.len()
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+// format a number of bytes for humans, e.g. 10.5 MiB
+fn format_size(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+
+    // find the largest unit the size fits in
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, units[unit])
+    } else {
+        format!("{:.1} {}", size, units[unit])
+    }
 }
 
-#[derive(Subcommand, Debug, Clone)]
-enum Commands {
-    Download {
-        #[arg()]
-        version: String,
-    },
-    Switch {
-        #[arg()]
-        version: String,
-    },
-    Current,
-    Purge {
-        #[arg()]
-        version: String,
-    },
+// remove a single version from the cache, a version that is not cached is only reported
+// returns the size of the removed archive
+fn purge_version(ctx: &Context, version: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    // get the path
+    let path = paths::archive(version)?;
+
+    // does the file exist
+    if !path.exists() {
+        ctx.reporter.warn(&format!("Version {} not found", version));
+
+        return Ok(0);
+    }
+
+    // pinned versions have to be unpinned first
+    if state::is_pinned(version)? {
+        ctx.reporter.warn(&format!(
+            "Version {} is pinned, unpin it to remove it",
+            version
+        ));
+
+        return Ok(0);
+    }
+
+    // remember the size to report the freed space
+    let size = fs::metadata(&path)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+
+    // remove the file
+    if fs::remove_file(path).is_err() {
+        return Err(format!("Failed to remove version: {}", version).into());
+    }
+
+    // the revision, download time and extracted files only describe the removed archive
+    let _ = fs::remove_file(paths::revision(version)?);
+    let _ = fs::remove_dir_all(paths::version_dir(version)?);
+    state::forget_download(version)?;
+
+    ctx.reporter
+        .success(&format!("Removed version {}", version));
+
+    Ok(size)
 }
 
-// the base url for neovim downloads
-static GITHUB_BASE_URL: &str = "https://github.com/neovim/neovim/releases/download/";
+// get the current version of nvim
+// the version is recorded in the data directory, so nvim is only run after a switch
+fn current(ctx: &Context) -> Result<String, Box<dyn std::error::Error>> {
+    // get the output directory
+    let output = paths::output_dir(ctx)?;
 
-fn main() {
-    // parse the arguments
-    let args = Args::parse();
+    // find the nvim executable
+    let nvim = output.join("bin/nvim");
 
-    // which command should we run
-    match args.cmd {
-        Commands::Download { version } => {
-            if let Err(error) = download(&version) {
-                println!("{}", error);
-            }
-        }
-        Commands::Switch { version } => {
-            if let Err(error) = switch(&version) {
-                println!("{}", error);
-            }
+    // does the file exist
+    if !nvim.exists() {
+        return Ok("None".to_string());
+    }
+
+    // running nvim is slow, so use the version recorded for this exact binary when there is one
+    let stamp = binary_stamp(&nvim);
+    if let (Some(stamp), Ok(Some((version, recorded)))) = (&stamp, state::current()) {
+        if *stamp == recorded {
+            return Ok(version);
         }
-        Commands::Current => {
-            // get the current version
-            match current() {
-                Ok(version) => {
-                    println!("Current version: {}", version);
-                }
-                Err(error) => {
-                    println!("{}", error);
+    }
+
+    // ask nvim itself and record the answer for the next time
+    let version = nvim_version(&nvim)?;
+    if let Some(stamp) = stamp {
+        let _ = state::set_current(&version, &stamp);
+    }
+
+    Ok(version)
+}
+
+// identify the binary the symlinks lead to, changes whenever a version is extracted
+fn binary_stamp(nvim: &Path) -> Option<String> {
+    let metadata = fs::metadata(nvim).ok()?;
+
+    Some(format!(
+        "{} {} {}.{} {}",
+        nvim.display(),
+        metadata.ino(),
+        metadata.mtime(),
+        metadata.mtime_nsec(),
+        metadata.size()
+    ))
+}
+
+// get the version by running nvim
+fn nvim_version(nvim: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    // get the version
+    let output = Command::new(nvim).arg("--version").output()?;
+
+    // the binary could not run, e.g. because the system glibc is too old
+    if !output.status.success() {
+        return Err(run_failure(&String::from_utf8_lossy(&output.stderr)).into());
+    }
+
+    // convert the output to a string
+    let output = String::from_utf8(output.stdout)?;
+
+    // version number is on the first line in this syntax: NVIM v0.11.0-dev
+    let version = output
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1));
+
+    // do we have a version
+    match version {
+        Some(version) => Ok(version.to_string()),
+        None => Err("Failed to get version".into()),
+    }
+}
+
+// explain why nvim failed to run based on its error output
+fn run_failure(stderr: &str) -> String {
+    // the loader reports e.g. /lib/x86_64-linux-gnu/libc.so.6: version `GLIBC_2.32' not found
+    match stderr.lines().find(|line| line.contains("GLIBC_")) {
+        Some(line) => format!(
+            "This build of nvim needs a newer glibc than this system has: {}\n\
+             Try the AppImage from https://github.com/neovim/neovim/releases or switch to an older version",
+            line.trim()
+        ),
+        None => format!("Failed to run nvim: {}", stderr.trim()),
+    }
+}
+
+// list the downloaded versions, marking the active and pinned ones
+fn list(ctx: &Context, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    // get the active version, a broken install simply has none
+    let active = active_version(ctx);
+
+    // get the pinned versions
+    let pins = state::pins()?;
+
+    // the active version is listed even when its archive was purged
+    let mut versions = paths::cached_versions()?;
+    if let Some(active) = active.as_ref().filter(|active| !versions.contains(active)) {
+        versions.push(active.clone());
+        versions.sort_by(|a, b| version::compare(a, b));
+    }
+
+    let mut entries = Vec::new();
+    for version in versions {
+        // get the size and download date of the archive
+        let metadata = fs::metadata(paths::archive(&version)?).ok();
+        let downloaded_at = metadata.as_ref().and_then(|_| downloaded_at(&version));
+
+        let is_active = active.as_deref() == Some(version.as_str());
+        let is_pinned = pins.contains(&version);
+
+        if json {
+            entries.push(json::Value::object(vec![
+                ("version", version.as_str().into()),
+                ("size_bytes", metadata.map(|metadata| metadata.len()).into()),
+                ("downloaded_at", downloaded_at.map(timestamp::format).into()),
+                ("active", is_active.into()),
+                ("pinned", is_pinned.into()),
+            ]));
+        } else {
+            ctx.reporter.output(&format!(
+                "{} {}{}{}",
+                if is_active { "*" } else { " " },
+                version,
+                if is_pinned { " (pinned)" } else { "" },
+                if metadata.is_none() {
+                    " (not cached)"
+                } else {
+                    ""
                 }
-            }
-        }
-        Commands::Purge { version } => {
-            if let Err(error) = purge(&version) {
-                println!("{}", error);
-            }
+            ));
         }
     }
+
+    if json {
+        ctx.reporter
+            .output(&json::Value::Array(entries).to_string());
+    }
+
+    Ok(())
 }
 
-// Download the specified version of nvim and store it in our cache
-fn download(version: &str) -> Result<Box<Path>, Box<dyn std::error::Error>> {
-    // get the file path
-    let path = path(version);
+// list the releases available for download, newest first
+fn list_remote(ctx: &Context, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    // get the versions we already have
+    let cached = paths::cached_versions()?;
+
+    let mut entries = Vec::new();
+    for release in github::releases(ctx)? {
+        // is there a build for this machine
+        let available = asset_name(ctx, &release.tag)
+            .is_ok_and(|name| release.assets.iter().any(|asset| asset.name == name));
+
+        // only the date of the publish time is of interest to humans
+        let published = release.published_at.clone().unwrap_or_default();
+        let date = published.split('T').next().unwrap_or_default();
+
+        let is_cached = cached.contains(&release.tag);
+
+        if json {
+            entries.push(json::Value::object(vec![
+                ("version", release.tag.as_str().into()),
+                ("published_at", release.published_at.into()),
+                ("prerelease", release.prerelease.into()),
+                ("available", available.into()),
+                ("downloaded", is_cached.into()),
+            ]));
+        } else {
+            ctx.reporter.output(&format!(
+                "{:<10} {:<10}{}{}{}",
+                release.tag,
+                date,
+                if release.prerelease {
+                    " (prerelease)"
+                } else {
+                    ""
+                },
+                if available {
+                    ""
+                } else {
+                    " (no build for this machine)"
+                },
+                if is_cached { " (downloaded)" } else { "" }
+            ));
+        }
+    }
 
-    // if the cache dir contains the version, there is no point in downloading it again
-    if path.exists() {
-        println!("Version {} already downloaded", version);
+    if json {
+        ctx.reporter
+            .output(&json::Value::Array(entries).to_string());
+    }
+
+    Ok(())
+}
+
+// let the user pick a version from the cached ones, newest first, and the remote ones when asked for
+fn pick_version(
+    ctx: &Context,
+    question: &str,
+    remote: bool,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut versions = paths::cached_versions()?;
+    versions.reverse();
+
+    // the list is still useful without the releases, so failing to get them is not fatal
+    if remote {
+        match github::releases(ctx) {
+            Ok(releases) => versions.extend(
+                releases
+                    .into_iter()
+                    .map(|release| release.tag)
+                    .filter(|tag| !versions.contains(tag))
+                    .collect::<Vec<String>>(),
+            ),
+            Err(error) => ctx
+                .reporter
+                .warn(&format!("Only listing the cached versions: {}", error)),
+        }
+    }
 
-        return Ok(path);
+    if versions.is_empty() {
+        return Err("No version given and there are no cached versions to pick from".into());
     }
 
-    // create the download url
-    let url = GITHUB_BASE_URL.to_string() + version + "/nvim-linux64.tar.gz";
+    prompt::pick(ctx, question, &versions)
+}
+
+// print every version worth completing, this runs on every tab so nothing is requested from github
+fn complete_versions(ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    let mut versions = paths::cached_versions()?;
 
-    println!("Pulling version {} of nvim from {}", version, url);
+    // the aliases and the tags with a name
+    versions.extend(state::aliases()?.into_iter().map(|(name, _)| name));
+    versions.extend(version::NAMED_TAGS.iter().map(|tag| tag.to_string()));
+
+    // the releases seen the last time they were fetched
+    if let Ok(tags) = fs::read_to_string(paths::release_tags()?) {
+        versions.extend(tags.lines().map(|tag| tag.trim().to_string()));
+    }
 
-    // attempt to download the file
-    let mut response = match reqwest::blocking::get(url) {
-        Ok(response) => response,
-        Err(_) => {
-            return Err("Failed to download version".into());
+    // every version once, in the order they were added
+    let mut seen = HashSet::new();
+    for version in versions {
+        if !version.is_empty() && seen.insert(version.clone()) {
+            ctx.reporter.output(&version);
         }
-    };
+    }
+
+    Ok(())
+}
 
-    // was it successful
-    if !response.status().is_success() {
-        return Err("Failed to download version".into());
+// show an overview of the install, the cache and anything that needs attention
+fn status(ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    let active = active_version(ctx);
+    let mut problems = Vec::new();
+
+    ctx.reporter.output(&format!(
+        "Active version:  {}",
+        active.as_deref().unwrap_or("None")
+    ));
+
+    if let Some(lock) = state::lock()? {
+        ctx.reporter.output(&format!(
+            "Locked at:       {}{}",
+            lock.version,
+            lock.reason
+                .map(|reason| format!(" ({})", reason))
+                .unwrap_or_default()
+        ));
     }
 
-    // create the file
-    let mut file = match File::create(path.clone()) {
-        Ok(file) => file,
-        Err(_) => {
-            return Err("Failed to store version".into());
+    // the newest release, being offline is not a problem of the install
+    match resolve_version(ctx, "stable") {
+        Ok(latest) => {
+            let behind = active
+                .as_deref()
+                .and_then(version::number)
+                .zip(version::number(&latest))
+                .is_some_and(|(active, latest)| active < latest);
+
+            ctx.reporter.output(&format!(
+                "Latest stable:   {}{}",
+                latest,
+                if behind { " (update available)" } else { "" }
+            ));
         }
-    };
+        Err(error) => ctx
+            .reporter
+            .output(&format!("Latest stable:   unknown ({})", error)),
+    }
+
+    // the downloads and the extracted version
+    let cached = paths::cached_versions()?;
+    let downloads: u64 = cached
+        .iter()
+        .map(|version| paths::archive(version).map_or(0, |path| paths::size(&path)))
+        .sum();
+    let dir = paths::output_dir(ctx)?;
+
+    ctx.reporter.output(&format!(
+        "Cached versions: {} ({})",
+        cached.len(),
+        format_size(downloads)
+    ));
+    ctx.reporter.output(&format!(
+        "Install:         {} in {}",
+        format_size(paths::size(&dir)),
+        dir.display()
+    ));
+
+    // symlinks that no longer lead to the active version
+    if let Some(manifest) = Manifest::load()? {
+        let broken = manifest
+            .links
+            .iter()
+            .filter(|link| !links::is_intact(link))
+            .count();
+
+        if broken > 0 {
+            problems.push(format!(
+                "{} symlinks are missing or broken, run `nvim_switcher repair`",
+                broken
+            ));
+        }
+    }
 
-    // write the file
-    if response.copy_to(&mut file).is_err() {
-        // remove the file
-        let _ = fs::remove_file(path);
+    // a nightly that was replaced upstream
+    if active.as_deref() == Some("nightly") {
+        let cached = fs::read_to_string(paths::revision("nightly")?).ok();
+        let upstream = upstream_revision(ctx, "nightly");
+
+        if upstream.is_some() && cached.map(|cached| cached.trim().to_string()) != upstream {
+            problems.push(
+                "A newer nightly build is available, run `nvim_switcher switch nightly`"
+                    .to_string(),
+            );
+        }
+    }
 
-        return Err("Failed to store version".into());
+    if problems.is_empty() {
+        ctx.reporter.output("Problems:        none");
     }
 
-    println!("Downloaded version {} of nvim", version);
+    for problem in problems {
+        ctx.reporter.warn(&problem);
+    }
 
-    Ok(path)
+    Ok(())
 }
 
-// Switch to the specified version of nvim
-fn switch(version: &str) -> Result<(), Box<dyn std::error::Error>> {
-    // is the current version the same as the one we are switching to
-    if version == current()? {
-        println!("Already using version {}", version);
+// show the release notes of a version, or of every release since an older version
+fn changelog(
+    ctx: &Context,
+    version: &str,
+    since: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let version = resolve_version(ctx, version)?;
+
+    // the notes of a single release
+    let since = match since {
+        Some(since) => since,
+        None => {
+            let release = github::release(ctx, &version)?;
+
+            return prompt::page(ctx, &release_notes(&release));
+        }
+    };
+
+    // an empty value means the active version
+    let since = if since.is_empty() {
+        match active_version(ctx) {
+            Some(active) => active,
+            None => return Err("No version installed to show the changes since".into()),
+        }
+    } else {
+        resolve_version(ctx, since)?
+    };
+
+    // only numbered versions have an order
+    let (from, to) = match (version::number(&since), version::number(&version)) {
+        (Some(from), Some(to)) => (from, to),
+        _ => {
+            return Err(format!(
+                "Cannot list the releases between {} and {}, use numbered versions",
+                since, version
+            )
+            .into());
+        }
+    };
+
+    // the releases after the older version up to the newer one, newest first
+    let mut releases: Vec<_> = github::releases(ctx)?
+        .into_iter()
+        .filter(|release| {
+            version::number(&release.tag).is_some_and(|number| from < number && number <= to)
+        })
+        .collect();
+    releases.sort_by(|a, b| version::compare(&b.tag, &a.tag));
+
+    if releases.is_empty() {
+        ctx.reporter.info(&format!(
+            "There are no releases after {} up to {}",
+            since, version
+        ));
 
         return Ok(());
     }
 
-    println!("Switching to version {}", version);
+    let notes: Vec<String> = releases.iter().map(release_notes).collect();
 
-    // get the path
-    let path = path(version);
+    prompt::page(ctx, &notes.join("\n"))
+}
 
-    // download the version if it is not already downloaded
-    if !path.exists() {
-        // download the version
-        if download(version).is_err() {
-            return Err(format!("Failed to download version {}", version).into());
+// format the notes of a release under its tag
+fn release_notes(release: &github::Release) -> String {
+    format!(
+        "# {}\n\n{}\n",
+        release.tag,
+        release
+            .body
+            .as_deref()
+            .map(|body| body.replace("\r\n", "\n"))
+            .unwrap_or_else(|| "No release notes".to_string())
+            .trim_end()
+    )
+}
+
+// show the details of a version, from the cache and from github
+fn info(ctx: &Context, version: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let version = &resolve_version(ctx, version)?;
+    let name = asset_name(ctx, version)?;
+
+    // the release on github, the local details are still useful without it
+    let release = match github::release(ctx, version) {
+        Ok(release) => Some(release),
+        Err(error) => {
+            ctx.reporter.warn(&error.to_string());
+
+            None
+        }
+    };
+    let asset = release
+        .as_ref()
+        .and_then(|release| release.assets.iter().find(|asset| asset.name == name));
+
+    // the cached archive
+    let metadata = fs::metadata(paths::archive(version)?).ok();
+    let yes_no = |value: bool| if value { "yes" } else { "no" };
+
+    ctx.reporter.output(&format!("Version:    {}", version));
+    ctx.reporter.output(&format!(
+        "Downloaded: {}",
+        match (&metadata, downloaded_at(version)) {
+            (Some(_), Some(seconds)) => format!("yes, {}", timestamp::format(seconds)),
+            (Some(_), None) => "yes".to_string(),
+            (None, _) => "no".to_string(),
         }
+    ));
+    if let Some(metadata) = &metadata {
+        ctx.reporter
+            .output(&format!("Size:       {}", format_size(metadata.len())));
     }
+    ctx.reporter.output(&format!(
+        "Active:     {}",
+        yes_no(active_version(ctx).as_ref() == Some(version))
+    ));
+    ctx.reporter.output(&format!(
+        "Pinned:     {}",
+        yes_no(state::is_pinned(version)?)
+    ));
+    ctx.reporter.output(&format!(
+        "Published:  {}",
+        release
+            .as_ref()
+            .and_then(|release| release.published_at.as_deref())
+            .unwrap_or("unknown")
+    ));
+    ctx.reporter.output(&format!(
+        "Asset:      {}{}",
+        name,
+        match (&release, asset) {
+            (Some(_), None) => " (not published for this release)",
+            _ => "",
+        }
+    ));
+    ctx.reporter.output(&format!(
+        "Checksum:   {}",
+        asset
+            .and_then(|asset| asset.digest.as_deref())
+            .unwrap_or("unknown")
+    ));
 
-    // remove the current version
-    if let Err(_) = fs::remove_dir_all(&output_dir()) {
-        return Err("Failed to remove current version".into());
-    }
+    Ok(())
+}
+
+// show the disk space used per cached version and by the install
+fn du(ctx: &Context, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    // the size of every archive
+    let mut versions = Vec::new();
+    for version in paths::cached_versions()? {
+        let size = paths::size(&paths::archive(&version)?);
 
-    // extract the version
-    if let Err(_) = extract(&path, &output_dir()) {
-        return Err("Failed to extract version".into());
+        versions.push((version, size));
     }
 
-    // construct the symlink path
-    let link_path = env::var("HOME").unwrap() + "/.local/";
+    // the extracted version
+    let dir = paths::output_dir(ctx)?;
+    let install = paths::size(&dir);
+
+    let total = versions.iter().map(|(_, size)| size).sum::<u64>() + install;
+
+    if json {
+        ctx.reporter.output(
+            &json::Value::object(vec![
+                (
+                    "versions",
+                    json::Value::Array(
+                        versions
+                            .into_iter()
+                            .map(|(version, size)| {
+                                json::Value::object(vec![
+                                    ("version", version.into()),
+                                    ("size_bytes", size.into()),
+                                ])
+                            })
+                            .collect(),
+                    ),
+                ),
+                (
+                    "install",
+                    json::Value::object(vec![
+                        ("path", dir.display().to_string().into()),
+                        ("size_bytes", install.into()),
+                    ]),
+                ),
+                ("total_bytes", total.into()),
+            ])
+            .to_string(),
+        );
 
-    // turn into path
-    let link = Path::new(&link_path);
+        return Ok(());
+    }
+
+    for (version, size) in &versions {
+        ctx.reporter
+            .output(&format!("{:<12} {:>10}", version, format_size(*size)));
+    }
+    ctx.reporter.output(&format!(
+        "{:<12} {:>10}  {}",
+        "install",
+        format_size(install),
+        dir.display()
+    ));
+    ctx.reporter
+        .output(&format!("{:<12} {:>10}", "total", format_size(total)));
 
-    // get the output dir
-    let dir = output_dir().join("nvim-linux64");
+    Ok(())
+}
 
-    // create symlinks for bin
-    symlinks(&dir.join("bin"), &link.join("bin"))?;
+// compare the active version to what is available upstream
+fn outdated(ctx: &Context, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let releases = github::releases(ctx)?;
+
+    // the newest stable release
+    let latest = releases
+        .iter()
+        .filter(|release| !release.prerelease)
+        .filter_map(|release| version::number(&release.tag).map(|number| (number, &release.tag)))
+        .max()
+        .map(|(_, tag)| tag.to_string());
+
+    // the newest nightly build for this machine, the asset is replaced on every build
+    let name = asset_name(ctx, "nightly")?;
+    let nightly = releases
+        .iter()
+        .find(|release| release.tag == "nightly")
+        .map(|release| {
+            let asset = release.assets.iter().find(|asset| asset.name == name);
+
+            (
+                asset
+                    .and_then(|asset| asset.updated_at.clone())
+                    .or_else(|| release.published_at.clone()),
+                asset.and_then(|asset| asset.revision()),
+            )
+        });
+    let (nightly_date, nightly_revision) = nightly.unwrap_or_default();
+
+    // is the active version behind, nothing is behind when no version is installed
+    let active = active_version(ctx);
+    let behind = match &active {
+        // compare the downloaded build to the newest one
+        Some(active) if active == "nightly" => Some(
+            nightly_revision.is_some()
+                && fs::read_to_string(paths::revision(active)?)
+                    .ok()
+                    .map(|cached| cached.trim().to_string())
+                    != nightly_revision,
+        ),
+        Some(active) => Some(
+            version::number(active)
+                .zip(latest.as_deref().and_then(version::number))
+                .is_some_and(|(active, latest)| active < latest),
+        ),
+        None => None,
+    };
 
-    // create symlinks for lib
-    symlinks(&dir.join("lib"), &link.join("lib"))?;
+    if json {
+        ctx.reporter.output(
+            &json::Value::object(vec![
+                ("current", active.into()),
+                ("latest_stable", latest.into()),
+                ("nightly_date", nightly_date.into()),
+                ("behind", behind.into()),
+            ])
+            .to_string(),
+        );
 
-    // loop over the share directory and create symlinks for all the files and folders
-    for entry in fs::read_dir(&dir.join("share"))? {
-        let entry = entry?;
+        return Ok(());
+    }
 
-        // create symlinks
-        symlinks(&entry.path(), &link.join("share").join(entry.file_name()))?;
+    // mark the line of the comparison the active version is behind on
+    let marker = |is_behind: bool| if is_behind { " (newer)" } else { "" };
+    let on_nightly = active.as_deref() == Some("nightly");
+
+    ctx.reporter.output(&format!(
+        "Current version: {}",
+        active.as_deref().unwrap_or("None")
+    ));
+    ctx.reporter.output(&format!(
+        "Latest stable:   {}{}",
+        latest.as_deref().unwrap_or("None"),
+        marker(!on_nightly && behind == Some(true))
+    ));
+    ctx.reporter.output(&format!(
+        "Latest nightly:  {}{}",
+        nightly_date
+            .as_deref()
+            .and_then(|date| date.split('T').next())
+            .unwrap_or("None"),
+        marker(on_nightly && behind == Some(true))
+    ));
+
+    if behind == Some(true) {
+        ctx.reporter.warn("The active version is outdated");
     }
 
-    println!("Switched to version {}", version);
+    Ok(())
+}
+
+// print the active version as json, with null fields when no version is installed
+fn current_json(ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    let version = Some(current(ctx)?).filter(|version| version != "None");
+    let tag = active_version(ctx).filter(|_| version.is_some());
+
+    // nightly builds follow the nightly tag, every other release is stable
+    let channel = tag.as_deref().map(|tag| match tag {
+        "nightly" => "nightly",
+        _ => "stable",
+    });
+
+    let dir = paths::output_dir(ctx)?;
+    let installed = |path: PathBuf| version.as_ref().map(|_| path.display().to_string());
+
+    ctx.reporter.output(
+        &json::Value::object(vec![
+            ("version", version.clone().into()),
+            ("tag", tag.clone().into()),
+            ("channel", channel.into()),
+            ("install_path", installed(dir.to_path_buf()).into()),
+            ("binary_path", installed(dir.join("bin/nvim")).into()),
+        ])
+        .to_string(),
+    );
 
     Ok(())
 }
 
-// create symlinks for all the files and folders in the directory
-fn symlinks(original: &Path, output: &Path) -> Result<(), Box<dyn std::error::Error>> {
-    // loop over the original directory
-    let dir = fs::read_dir(original)?;
+// the exit status of which when the version is not installed
+static NOT_INSTALLED: i32 = 3;
+
+// get the path to the nvim binary of an installed version, the active one when none is given
+// none when the version is neither active nor extracted to run without switching to it
+fn which(
+    ctx: &Context,
+    version: Option<&str>,
+) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    let active = active_version(ctx);
+    let version = match version {
+        Some(version) => resolve_version(ctx, version)?,
+        None => match &active {
+            Some(active) => active.clone(),
+            None => return Ok(None),
+        },
+    };
 
-    // loop over the entries
-    for entry in dir {
-        let entry = entry?;
+    if active.as_ref() == Some(&version) {
+        return Ok(Some(paths::output_dir(ctx)?.join("bin/nvim")));
+    }
 
-        // get the file name
-        let file_name = entry.file_name();
+    let nvim = paths::version_dir(&version)?.join("bin/nvim");
 
-        // determine the name of the symlink
-        let link = output.join(&file_name);
+    Ok(nvim.exists().then_some(nvim))
+}
+
+// get the tag of the active version, e.g. nightly rather than the version nvim reports
+fn active_version(ctx: &Context) -> Option<String> {
+    // fall back to asking nvim itself
+    switched_version(ctx).or_else(|| current(ctx).ok().filter(|version| version != "None"))
+}
+
+// get the tag that was switched to from the manifest, as long as the install still exists
+fn switched_version(ctx: &Context) -> Option<String> {
+    let manifest = Manifest::load().ok()??;
 
-        // create the output directory if it does not exist
-        if !output.exists() {
-            fs::create_dir_all(&output)?;
+    paths::output_dir(ctx)
+        .is_ok_and(|dir| dir.join("bin/nvim").exists())
+        .then_some(manifest.version)
+}
+
+// create, show, remove or list aliases
+fn alias(
+    ctx: &Context,
+    name: Option<&str>,
+    version: Option<&str>,
+    remove: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match (name, version) {
+        // remove the alias
+        (Some(name), _) if remove => {
+            if state::remove_alias(name)? {
+                ctx.reporter.success(&format!("Removed alias {}", name));
+            } else {
+                ctx.reporter.info(&format!("There is no alias {}", name));
+            }
         }
+        // create the alias, the version is stored as given and resolved when the alias is used
+        (Some(name), Some(version)) => {
+            if !version::is_valid_alias(name) {
+                return Err(format!(
+                    "Invalid alias: {} (use letters, digits, - and _, and not a version)",
+                    name
+                )
+                .into());
+            }
 
-        // does the file already exist
-        if link.exists() {
-            // remove the file
-            fs::remove_file(&link)?;
+            // refuse versions that will never resolve
+            if requirement::is_requirement(version) {
+                requirement::Requirement::parse(version)?;
+            } else if version::partial(version).is_none() {
+                version::normalize(version)?;
+            }
+
+            state::set_alias(name, version.trim())?;
+
+            ctx.reporter
+                .success(&format!("Alias {} now stands for {}", name, version.trim()));
         }
+        // show a single alias
+        (Some(name), None) => match state::alias(name)? {
+            Some(version) => ctx.reporter.output(&version),
+            None => return Err(format!("There is no alias {}", name).into()),
+        },
+        // list all the aliases
+        (None, _) => {
+            let aliases = state::aliases()?;
+
+            if aliases.is_empty() {
+                ctx.reporter.info("No aliases defined");
+            }
 
-        // create the symlink
-        os::unix::fs::symlink(entry.path(), link.clone())?;
+            for (name, version) in aliases {
+                ctx.reporter.output(&format!("{} -> {}", name, version));
+            }
+        }
     }
 
     Ok(())
 }
 
-fn extract(file: &Path, output_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
-    // open the file
-    let file = File::open(file)?;
+// set, show or remove the default version
+fn default_version(
+    ctx: &Context,
+    version: Option<&str>,
+    unset: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if unset {
+        state::set_default_version(None)?;
+        ctx.reporter.success("Removed the default version");
 
-    // decompress the file
-    let decompressed = GzDecoder::new(BufReader::new(file));
+        return Ok(());
+    }
 
-    // create the archive to read the content
-    let mut archive = Archive::new(decompressed);
+    match version {
+        // store the tag, so restoring does not depend on what the newest release is by then
+        Some(version) => {
+            let version = resolve_version(ctx, version)?;
+            state::set_default_version(Some(&version))?;
 
-    // extract the content
-    archive.unpack(output_dir)?;
+            ctx.reporter
+                .success(&format!("Default version is now {}", version));
+        }
+        None => match state::default_version()? {
+            Some(version) => ctx.reporter.output(&version),
+            None => ctx.reporter.info("No default version set"),
+        },
+    }
 
     Ok(())
 }
 
-// remove a version from the cache
-fn purge(version: &str) -> Result<(), Box<dyn std::error::Error>> {
-    // get the path
-    let path = path(version);
-
-    // does the file exist
-    if path.exists() {
-        // remove the file
-        if let Err(_) = fs::remove_file(path) {
-            return Err(format!("Failed to remove version: {}", version).into());
+// reinstall the default version when the installed nvim is gone, nothing happens while it is there
+fn restore(ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    let version = match state::default_version()? {
+        Some(version) => version,
+        None => {
+            return Err("No default version set, run `nvim_switcher default VERSION` first".into())
         }
+    };
+
+    if paths::output_dir(ctx)?.join("bin/nvim").exists() {
+        ctx.reporter
+            .info("The installed version is intact, nothing to restore");
+
+        return Ok(());
+    }
+
+    ctx.reporter.info(&format!(
+        "The installed version is missing, restoring {}",
+        version
+    ));
+
+    switch(ctx, &version)
+}
+
+// pin a version so it is kept in the cache
+fn pin(ctx: &Context, version: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // resolve the version into the upstream tag
+    let version = &resolve_version(ctx, version)?;
+
+    if state::pin(version)? {
+        ctx.reporter.success(&format!("Pinned version {}", version));
     } else {
-        return Err(format!("Version {} not found", version).into());
+        ctx.reporter
+            .info(&format!("Version {} is already pinned", version));
     }
 
     Ok(())
 }
 
-// get the path to the version
-fn path(version: &str) -> Box<Path> {
-    // get the cache dir
-    let cache_dir = cache_dir();
+// unpin a version so it can be removed from the cache again
+fn unpin(ctx: &Context, version: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // resolve the version into the upstream tag
+    let version = &resolve_version(ctx, version)?;
 
-    // create the file path
-    let path = cache_dir.join(format!("nvim-{}.tar.gz", version));
+    if state::unpin(version)? {
+        ctx.reporter
+            .success(&format!("Unpinned version {}", version));
+    } else {
+        ctx.reporter
+            .info(&format!("Version {} is not pinned", version));
+    }
 
-    // create the path
-    Path::new(&path).into()
+    Ok(())
 }
 
-// get the cache directory
-fn cache_dir() -> Box<Path> {
-    // get the cache directory from the env var
-    // with a backup to the home directory
-    let mut dir =
-        env::var("XDG_CACHE_HOME").unwrap_or_else(|_| env::var("HOME").unwrap() + "/.cache");
+// is the given version the active one
+// this runs in shell prompts, so it is decided from the manifest and the cache without a request
+fn is_current(ctx: &Context, version: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let version = state::alias(version.trim())?.unwrap_or_else(|| version.trim().to_string());
+
+    // without a manifest only nvim itself can tell, it reports a numbered version even for nightly
+    let active = match switched_version(ctx) {
+        Some(active) => active,
+        None => return Ok(resolve_version(ctx, &version)? == current(ctx)?),
+    };
 
-    // add our directory to the cache
-    dir += "/nvim_switcher";
+    // the versions known to exist, a newer one means stable or a partial version is not the active one
+    let mut known = paths::cached_versions()?;
+    if let Ok(tags) = fs::read_to_string(paths::release_tags()?) {
+        known.extend(tags.lines().map(|tag| tag.trim().to_string()));
+    }
 
-    // create the path
-    let path = Path::new(&dir);
+    Ok(version::is_active(&version, &active, &known))
+}
 
-    // does the directory exist
-    if !path.exists() {
-        // create the directory
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+    use report::{CaptureReporter, Level};
+
+    // a context reporting into the returned reporter, with every option at its default
+    fn capture() -> (Rc<CaptureReporter>, Context) {
+        let capture = Rc::new(CaptureReporter::default());
+        let ctx = Context {
+            reporter: capture.clone(),
+            install_dir: None,
+            offline: Rc::new(Cell::new(true)),
+            prefer_ipv4: false,
+            proxy: None,
+            ca_certificates: Vec::new(),
+            minisign_key: None,
+            gpg_key: None,
+            require_signed: false,
+            verify_attestation: false,
+            stream: false,
+            keep_archive: false,
+            yes: false,
+            no_input: true,
+            asset_template: None,
+            base_urls: Vec::new(),
+            github_url: github::GITHUB_URL.to_string(),
+            api_url: github::api_url(github::GITHUB_URL),
+            repo: None,
+            bin_aliases: Vec::new(),
+            github_token: None,
+            keep_versions: None,
+            connect_timeout: http::DEFAULT_CONNECT_TIMEOUT,
+            read_timeout: http::DEFAULT_READ_TIMEOUT,
+            retries: 0,
+            connections: 1,
+            limit_rate: None,
+        };
+
+        (capture, ctx)
+    }
+
+    #[test]
+    fn completions_are_reported_as_output() {
+        let (capture, ctx) = capture();
+        run(&ctx, Commands::Completions { shell: Shell::Bash }).unwrap();
+
+        let messages = capture.messages();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].0, Level::Output);
+        assert!(messages[0].1.contains("nvim_switcher"));
+    }
+
+    #[test]
+    fn manpage_reports_what_it_wrote() {
+        let (capture, ctx) = capture();
+        let dir = temp_dir("manpage");
+        run(&ctx, Commands::Manpage { dir: dir.clone() }).unwrap();
+
+        let pages = fs::read_dir(&dir).unwrap().count();
+        assert!(pages > 1);
+        assert_eq!(
+            capture.messages(),
+            vec![(
+                Level::Success,
+                format!("Wrote {} man pages to {}", pages, dir.display())
+            )]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // a directory of its own for a test, removed first in case an earlier run left it behind
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nvim_switcher-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
         fs::create_dir_all(&dir).unwrap();
+
+        dir
     }
 
-    path.into()
-}
+    #[test]
+    fn an_install_dir_holding_other_files_is_not_removed() {
+        let (_, mut ctx) = capture();
+        let dir = temp_dir("install-dir");
+        ctx.install_dir = Some(dir.clone());
 
-// get the output dir of the current version
-fn output_dir() -> Box<Path> {
-    // format the output directory
-    let path = cache_dir().join("current");
+        // empty, or holding only a release, it is ours to replace
+        paths::check_output_dir(&ctx, &dir).unwrap();
+        fs::create_dir_all(dir.join("bin")).unwrap();
+        paths::check_output_dir(&ctx, &dir).unwrap();
 
-    // does the directory exist
-    if !path.exists() {
-        // create the directory
-        fs::create_dir_all(&path).unwrap();
+        // e.g. /usr/local given instead of /usr/local/nvim
+        fs::create_dir_all(dir.join("include")).unwrap();
+        assert!(paths::check_output_dir(&ctx, &dir).is_err());
+
+        // unless an earlier extraction marked it
+        paths::mark_output_dir(&dir).unwrap();
+        paths::check_output_dir(&ctx, &dir).unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
     }
 
-    path.into()
-}
+    #[test]
+    fn which_finds_an_installed_version_that_is_not_active() {
+        let (_, ctx) = capture();
+        let dir = temp_dir("which");
+        env::set_var("XDG_CACHE_HOME", dir.join("cache"));
+        env::set_var("XDG_DATA_HOME", dir.join("data"));
 
-// get the current version of nvim
-fn current() -> Result<String, Box<dyn std::error::Error>> {
-    // get the output directory
-    let output = output_dir();
+        // extracted to run without switching to it, e.g. by exec
+        let nvim = paths::version_dir("v0.9.5").unwrap().join("bin/nvim");
+        fs::create_dir_all(nvim.parent().unwrap()).unwrap();
+        fs::write(&nvim, "").unwrap();
 
-    // find the nvim executable
-    let nvim = output.join("nvim-linux64/bin/nvim");
+        assert_eq!(which(&ctx, Some("0.9.5")).unwrap(), Some(nvim));
+        assert_eq!(which(&ctx, Some("v0.10.0")).unwrap(), None);
+        assert_eq!(which(&ctx, None).unwrap(), None);
 
-    // does the file exist
-    if !nvim.exists() {
-        return Ok("None".to_string());
+        fs::remove_dir_all(&dir).unwrap();
     }
 
-    // get the version
-    let output = Command::new(nvim).arg("--version").output()?;
+    // answer a single request on a local port with the body, returning the url it is served at
+    fn serve(body: &'static str) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let read = stream.read(&mut buffer).unwrap();
+                request.extend_from_slice(&buffer[..read]);
+            }
 
-    // convert the output to a string
-    let output = String::from_utf8(output.stdout)?;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
 
-    // version number is on the first line in this syntax: NVIM v0.11.0-dev
-    let version = output.lines().next().unwrap().split_whitespace().nth(1);
+        url
+    }
 
-    // do we have a version
-    match version {
-        Some(version) => {
-            return Ok(version.to_string());
+    #[test]
+    fn an_unresolvable_mirror_falls_through_to_the_next() {
+        let (_, ctx) = capture();
+        ctx.offline.set(false);
+
+        let mirrors = ["http://nvim-switcher.invalid".to_string(), serve("served")];
+        let mut responses = Vec::new();
+        for mirror in &mirrors {
+            let client = http::client(&ctx).unwrap();
+            responses.push(http::send(&ctx, client.get(mirror)).map(|response| response.text()));
         }
-        None => {
-            return Err("Failed to get version".into());
+
+        // the first mirror fails alone, the second is still requested
+        let failure = responses[0].as_ref().err().unwrap();
+        assert_eq!(failure.message, "the host name could not be resolved");
+        assert!(!failure.retryable);
+        assert!(!ctx.offline.get());
+        assert_eq!(
+            responses[1].as_ref().ok().unwrap().as_ref().unwrap(),
+            "served"
+        );
+    }
+
+    // bytes that do not compress, generated as they are read so the fixture is never held in memory
+    struct Noise {
+        state: u64,
+        left: u64,
+    }
+
+    impl Noise {
+        fn new(size: u64) -> Self {
+            Noise {
+                state: 0x2545f4914f6cdd1d,
+                left: size,
+            }
+        }
+    }
+
+    impl Read for Noise {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            let count = buffer.len().min(self.left as usize);
+            for byte in &mut buffer[..count] {
+                self.state ^= self.state << 13;
+                self.state ^= self.state >> 7;
+                self.state ^= self.state << 17;
+                *byte = self.state as u8;
+            }
+            self.left -= count as u64;
+
+            Ok(count)
+        }
+    }
+
+    #[test]
+    fn extract_streams_a_large_archive() {
+        let dir = temp_dir("extract");
+        let archive = dir.join("nvim-v0.10.0.tar.gz");
+
+        // far larger than the buffers, so it only extracts correctly when streamed through them
+        let size = 24 * 1024 * 1024 + 123;
+        let encoder = GzEncoder::new(File::create(&archive).unwrap(), Compression::fast());
+        let mut builder = tar::Builder::new(encoder);
+        for (path, size) in [
+            ("nvim-linux64/bin/nvim", 16),
+            ("nvim-linux64/share/big", size),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(size);
+            header.set_mode(0o755);
+            builder
+                .append_data(&mut header, path, Noise::new(size))
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let output = dir.join("out");
+        fs::create_dir_all(&output).unwrap();
+        extract(&archive, &output).unwrap();
+
+        // the top level directory is dropped and the content matches what was written, compared in parts
+        assert!(output.join("bin/nvim").is_file());
+        let mut extracted =
+            BufReader::with_capacity(BUFFER_SIZE, File::open(output.join("share/big")).unwrap());
+        let mut expected = Noise::new(size);
+        let (mut actual, mut wanted) = (vec![0; BUFFER_SIZE], vec![0; BUFFER_SIZE]);
+        let mut total = 0;
+        loop {
+            let read = expected.read(&mut wanted).unwrap();
+            extracted.read_exact(&mut actual[..read]).unwrap();
+            assert_eq!(actual[..read], wanted[..read]);
+            total += read as u64;
+
+            if read == 0 {
+                break;
+            }
         }
+        assert_eq!(total, size);
+        assert_eq!(extracted.read(&mut actual).unwrap(), 0);
+
+        fs::remove_dir_all(&dir).unwrap();
     }
 }
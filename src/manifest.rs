@@ -0,0 +1,60 @@
+use std::{fs, path::PathBuf};
+
+use crate::{links::Link, paths};
+
+// the symlinks created for the active version, so they can be checked and cleaned up later
+pub struct Manifest {
+    pub version: String,
+    pub links: Vec<Link>,
+}
+
+// get the path to the manifest file
+fn path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(paths::data_dir()?.join("manifest"))
+}
+
+impl Manifest {
+    // read the manifest, there is none before the first switch
+    pub fn load() -> Result<Option<Manifest>, Box<dyn std::error::Error>> {
+        // read the file
+        let content = match fs::read_to_string(path()?) {
+            Ok(content) => content,
+            Err(_) => return Ok(None),
+        };
+
+        let mut lines = content.lines();
+
+        // the first line holds the version
+        let version = match lines.next().and_then(|line| line.strip_prefix("version ")) {
+            Some(version) => version.to_string(),
+            None => return Err("The manifest is corrupted".into()),
+        };
+
+        // every other line holds a link and its target separated by a tab
+        let mut links = Vec::new();
+        for line in lines {
+            match line.split_once('\t') {
+                Some((link, target)) => links.push(Link {
+                    link: link.into(),
+                    target: target.into(),
+                }),
+                None => return Err("The manifest is corrupted".into()),
+            }
+        }
+
+        Ok(Some(Manifest { version, links }))
+    }
+
+    // write the manifest
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut content = format!("version {}\n", self.version);
+
+        for link in &self.links {
+            content += &format!("{}\t{}\n", link.link.display(), link.target.display());
+        }
+
+        fs::write(path()?, content)?;
+
+        Ok(())
+    }
+}
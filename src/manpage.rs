@@ -0,0 +1,161 @@
+// man pages in roff generated from the command line definition
+use std::{fs, path::Path};
+
+use clap::{Arg, Command};
+
+use crate::paths;
+
+// the name the pages are installed under
+static PROGRAM: &str = "nvim_switcher";
+
+// escape text so roff prints it as is
+fn escape(text: &str) -> String {
+    let text = text.replace('\\', "\\e").replace('-', "\\-");
+
+    // a line starting with a dot or quote would be read as a request
+    text.lines()
+        .map(|line| {
+            if line.starts_with('.') || line.starts_with('\'') {
+                format!("\\&{}", line)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// get the full help of a command or argument
+fn help(text: Option<&clap::builder::StyledStr>) -> String {
+    text.map(|text| text.to_string()).unwrap_or_default()
+}
+
+// get how an argument is written, e.g. -y, --yes or --install-dir INSTALL_DIR
+fn usage(arg: &Arg) -> String {
+    let value = arg
+        .get_value_names()
+        .and_then(|names| names.first())
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| arg.get_id().as_str().to_uppercase());
+
+    if arg.is_positional() {
+        return format!("\\fI{}\\fR", escape(&value));
+    }
+
+    let mut names = Vec::new();
+    if let Some(short) = arg.get_short() {
+        names.push(format!("\\fB\\-{}\\fR", short));
+    }
+    if let Some(long) = arg.get_long() {
+        names.push(format!("\\fB\\-\\-{}\\fR", escape(long)));
+    }
+
+    let mut usage = names.join(", ");
+    if arg.get_action().takes_values() {
+        usage += &format!(" \\fI{}\\fR", escape(&value));
+    }
+
+    usage
+}
+
+// a section listing arguments with their help
+fn arguments<'a>(title: &str, args: impl Iterator<Item = &'a Arg>) -> String {
+    let mut section = String::new();
+    for arg in args.filter(|arg| !arg.is_hide_set()) {
+        section += &format!(".TP\n{}\n", usage(arg));
+
+        let help = help(arg.get_long_help().or(arg.get_help()));
+        if !help.is_empty() {
+            section += &format!("{}\n", escape(&help));
+        }
+    }
+
+    if section.is_empty() {
+        return section;
+    }
+
+    format!(".SH {}\n{}", title, section)
+}
+
+// the subcommands of a command that get a page of their own
+fn subcommands(command: &Command) -> Vec<&Command> {
+    command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set() && subcommand.get_name() != "help")
+        .collect()
+}
+
+// render the page of a command, the name is the one the page is installed under
+fn page(command: &Command, name: &str, version: &str) -> String {
+    let about = help(command.get_about());
+
+    let mut page = format!(
+        ".TH {} 1 \"\" \"{} {}\"\n.SH NAME\n{}",
+        escape(&name.to_uppercase()),
+        PROGRAM,
+        escape(version),
+        escape(name)
+    );
+    if !about.is_empty() {
+        page += &format!(" \\- {}", escape(&about));
+    }
+
+    // the usage clap prints, without the heading
+    let synopsis = command.clone().render_usage().to_string();
+    let synopsis = synopsis.strip_prefix("Usage: ").unwrap_or(&synopsis);
+    page += &format!("\n.SH SYNOPSIS\n{}\n", escape(synopsis));
+
+    if let Some(description) = command.get_long_about() {
+        page += &format!(".SH DESCRIPTION\n{}\n", escape(&description.to_string()));
+    }
+
+    page += &arguments("ARGUMENTS", command.get_positionals());
+    page += &arguments(
+        "OPTIONS",
+        command.get_arguments().filter(|arg| !arg.is_positional()),
+    );
+
+    // point to the pages of the subcommands
+    let subcommands = subcommands(command);
+    if !subcommands.is_empty() {
+        page += ".SH COMMANDS\n";
+        for subcommand in subcommands {
+            page += &format!(
+                ".TP\n\\fB{}\\-{}\\fR(1)\n{}\n",
+                PROGRAM,
+                escape(subcommand.get_name()),
+                escape(&help(subcommand.get_about()))
+            );
+        }
+    }
+
+    page
+}
+
+// write a page for the program and one for every subcommand, returns the number of pages written
+pub fn write(mut command: Command, dir: &Path) -> Result<usize, Box<dyn std::error::Error>> {
+    // building propagates the global options and adds the help options
+    command.build();
+
+    let version = command.get_version().unwrap_or_default().to_string();
+
+    paths::ensure_dir(dir)?;
+
+    let mut pages = vec![(PROGRAM.to_string(), page(&command, PROGRAM, &version))];
+    for subcommand in subcommands(&command) {
+        let name = format!("{}-{}", PROGRAM, subcommand.get_name());
+        let page = page(subcommand, &name, &version);
+
+        pages.push((name, page));
+    }
+
+    for (name, page) in &pages {
+        let path = dir.join(format!("{}.1", name));
+
+        if fs::write(&path, page).is_err() {
+            return Err(format!("Failed to write {}", path.display()).into());
+        }
+    }
+
+    Ok(pages.len())
+}
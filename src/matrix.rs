@@ -0,0 +1,61 @@
+// run a command against several versions, like a ci matrix
+use crate::{context::Context, extracted, paths, version_command};
+
+// run the command once for every version and report which ones it failed on
+// the versions default to every cached version
+pub fn matrix(
+    ctx: &Context,
+    versions: &[String],
+    command: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let versions = if versions.is_empty() {
+        paths::cached_versions()?
+    } else {
+        versions.to_vec()
+    };
+
+    if versions.is_empty() {
+        return Err("There are no cached versions to run the command for".into());
+    }
+
+    // the outcome for every version, a version that cannot be run counts as failed
+    let mut results = Vec::new();
+    for version in &versions {
+        ctx.reporter
+            .info(&format!("Running {} with {}", command.join(" "), version));
+
+        let outcome = match extracted(ctx, version) {
+            Ok(dir) => match version_command(&dir, command)?.status() {
+                Ok(status) if status.success() => Ok(()),
+                Ok(status) => match status.code() {
+                    Some(code) => Err(format!("exited with {}", code)),
+                    None => Err("was killed".to_string()),
+                },
+                Err(error) => Err(format!("could not be started: {}", error)),
+            },
+            Err(error) => Err(error.to_string()),
+        };
+
+        results.push((version, outcome));
+    }
+
+    // the summary
+    for (version, outcome) in &results {
+        match outcome {
+            Ok(()) => ctx.reporter.output(&format!("{:<10} pass", version)),
+            Err(reason) => ctx
+                .reporter
+                .output(&format!("{:<10} fail ({})", version, reason)),
+        }
+    }
+
+    let failed = results
+        .iter()
+        .filter(|(_, outcome)| outcome.is_err())
+        .count();
+    if failed > 0 {
+        return Err(format!("Failed on {} of {} versions", failed, results.len()).into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,317 @@
+// adopt the nvim installs of other tools and manual setups into the cache
+use std::{
+    env,
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+    process::{self, Command, Stdio},
+    time::SystemTime,
+};
+
+use flate2::{write::GzEncoder, Compression};
+
+use crate::{
+    context::Context, format_size, nvim_version, paths, prompt, state, switch, timestamp,
+    BUFFER_SIZE,
+};
+
+// an install found on the system
+struct Install {
+    // where it was found, removed with remove
+    source: PathBuf,
+    // where the bin, lib and share directories are, the same as the source unless it is an appimage
+    root: PathBuf,
+    version: String,
+}
+
+// get the directory holding bin/nvim, the directory itself or one level deeper
+// e.g. bob keeps the extracted nvim-linux64 directory inside the directory of the version
+fn find_root(dir: &Path) -> Option<PathBuf> {
+    if dir.join("bin/nvim").is_file() {
+        return Some(dir.to_path_buf());
+    }
+
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| path.join("bin/nvim").is_file())
+}
+
+// is the file name that of an appimage of nvim
+fn is_appimage(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    name.ends_with(".appimage") && name.contains("nvim")
+}
+
+// list the places other tools and manual installs keep nvim
+fn candidates() -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let home = PathBuf::from(paths::home_dir()?);
+    let data = match env::var("XDG_DATA_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => home.join(".local/share"),
+    };
+
+    let mut found = Vec::new();
+    let mut entries = |dir: &Path, matches: &dyn Fn(&Path) -> bool| {
+        if let Ok(entries) = fs::read_dir(dir) {
+            let mut paths: Vec<PathBuf> = entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| matches(path))
+                .collect();
+            paths.sort();
+
+            found.extend(paths);
+        }
+    };
+
+    let named = |path: &Path| {
+        path.file_name().is_some_and(|name| {
+            let name = name.to_string_lossy().to_lowercase();
+
+            name.contains("nvim") || name.contains("neovim")
+        })
+    };
+
+    // bob keeps every version in a directory of its own
+    entries(&data.join("bob"), &|path| path.is_dir());
+
+    // tarballs extracted by hand
+    entries(Path::new("/opt"), &|path| path.is_dir() && named(path));
+    entries(&home, &|path| path.is_dir() && named(path));
+    entries(&home.join(".local/opt"), &|path| {
+        path.is_dir() && named(path)
+    });
+
+    // appimages
+    for dir in [
+        home.join("Applications"),
+        home.join(".local/bin"),
+        home.join("bin"),
+        PathBuf::from("/opt"),
+        PathBuf::from("/usr/local/bin"),
+    ] {
+        entries(&dir, &|path| path.is_file() && is_appimage(path));
+    }
+
+    Ok(found)
+}
+
+// extract an appimage into a temporary directory, returns the directory holding bin, lib and share
+fn extract_appimage(appimage: &Path, temp: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    paths::ensure_dir(temp)?;
+
+    let status = Command::new(appimage)
+        .arg("--appimage-extract")
+        .current_dir(temp)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    if !status.is_ok_and(|status| status.success()) {
+        return Err(format!("Failed to extract {}", appimage.display()).into());
+    }
+
+    match find_root(&temp.join("squashfs-root/usr")) {
+        Some(root) => Ok(root),
+        None => Err(format!("{} does not contain nvim", appimage.display()).into()),
+    }
+}
+
+// determine what is installed at a path, none when it is not an nvim install
+fn inspect(path: &Path, temp: &Path) -> Result<Option<Install>, Box<dyn std::error::Error>> {
+    let root = if path.is_file() {
+        extract_appimage(path, temp)?
+    } else {
+        match find_root(path) {
+            Some(root) => root,
+            None => return Ok(None),
+        }
+    };
+
+    // the layout switching links into
+    if !root.join("lib").is_dir() || !root.join("share").is_dir() {
+        return Ok(None);
+    }
+
+    // builds of the development branch are what nightly is
+    let version = nvim_version(&root.join("bin/nvim"))?;
+    let version = if version.contains("-dev") {
+        "nightly".to_string()
+    } else {
+        version
+    };
+
+    if version.contains('/') || version.starts_with('.') {
+        return Err(format!("{} reports an invalid version", path.display()).into());
+    }
+
+    Ok(Some(Install {
+        source: path.to_path_buf(),
+        root,
+        version,
+    }))
+}
+
+// pack the install into an archive in the cache, as if it was downloaded
+fn adopt(install: &Install) -> Result<u64, Box<dyn std::error::Error>> {
+    let archive = paths::archive(&install.version)?;
+    let partial = archive.with_extension("gz.partial");
+
+    // the archive has the top level directory extract strips
+    let pack = || -> Result<(), Box<dyn std::error::Error>> {
+        let file = BufWriter::with_capacity(BUFFER_SIZE, File::create(&partial)?);
+        let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        builder.follow_symlinks(false);
+        builder.append_dir_all(format!("nvim-{}", install.version), &install.root)?;
+        builder.into_inner()?.finish()?;
+
+        Ok(())
+    };
+
+    if pack().is_err() {
+        let _ = fs::remove_file(&partial);
+
+        return Err(format!("Failed to pack {}", install.source.display()).into());
+    }
+
+    fs::rename(&partial, &archive)?;
+    state::record_download(&install.version, timestamp::seconds(SystemTime::now()))?;
+
+    Ok(fs::metadata(&archive)?.len())
+}
+
+// find the installs of other tools, or look at the given paths, and import the ones the user confirms
+// with remove the originals are deleted once imported
+// when the nvim on the path was one of them, offer to switch so nvim_switcher takes over its symlink
+pub fn migrate(
+    ctx: &Context,
+    given: &[PathBuf],
+    remove: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let search = if given.is_empty() {
+        candidates()?
+    } else {
+        given.to_vec()
+    };
+
+    let temp = env::temp_dir().join(format!("nvim_switcher-migrate-{}", process::id()));
+    let result = (|| -> Result<(), Box<dyn std::error::Error>> {
+        let mut installs = Vec::new();
+        for (index, path) in search.iter().enumerate() {
+            // every appimage is extracted into a directory of its own
+            match inspect(path, &temp.join(index.to_string())) {
+                Ok(Some(install)) => installs.push(install),
+                Ok(None) if !given.is_empty() => ctx
+                    .reporter
+                    .warn(&format!("{} is not an nvim install", path.display())),
+                Ok(None) => {}
+                Err(error) => ctx.reporter.warn(&error.to_string()),
+            }
+        }
+
+        if installs.is_empty() {
+            ctx.reporter.info("No other nvim installs found");
+
+            return Ok(());
+        }
+
+        // the nvim on the path still belongs to the other tool until we switch
+        // found before anything is removed, the link no longer resolves after that
+        let link = paths::link_dir()?.join("bin/nvim");
+        let owner = fs::canonicalize(&link).ok().and_then(|target| {
+            installs.iter().position(|install| {
+                fs::canonicalize(&install.source).is_ok_and(|source| target.starts_with(source))
+            })
+        });
+
+        let cached = paths::cached_versions()?;
+        let mut imported = Vec::new();
+        for install in &installs {
+            if cached.contains(&install.version) || imported.contains(&install.version) {
+                ctx.reporter.info(&format!(
+                    "Skipping {}, version {} is already cached",
+                    install.source.display(),
+                    install.version
+                ));
+
+                continue;
+            }
+
+            if !prompt::confirm(
+                ctx,
+                &format!(
+                    "Import version {} from {}?",
+                    install.version,
+                    install.source.display()
+                ),
+            )? {
+                continue;
+            }
+
+            let size = adopt(install)?;
+            imported.push(install.version.clone());
+
+            ctx.reporter.success(&format!(
+                "Imported version {} ({}) from {}",
+                install.version,
+                format_size(size),
+                install.source.display()
+            ));
+
+            if remove {
+                let removed = if install.source.is_dir() {
+                    fs::remove_dir_all(&install.source)
+                } else {
+                    fs::remove_file(&install.source)
+                };
+
+                match removed {
+                    Ok(()) => ctx
+                        .reporter
+                        .info(&format!("Removed {}", install.source.display())),
+                    Err(error) => ctx.reporter.warn(&format!(
+                        "Failed to remove {}: {}",
+                        install.source.display(),
+                        error
+                    )),
+                }
+            }
+        }
+
+        if imported.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(install) = owner
+            .map(|index| &installs[index])
+            .filter(|install| imported.contains(&install.version))
+        {
+            if prompt::confirm(
+                ctx,
+                &format!(
+                    "{} points at {}, switch to version {} instead?",
+                    link.display(),
+                    install.source.display(),
+                    install.version
+                ),
+            )? {
+                return switch(ctx, &install.version);
+            }
+        }
+
+        ctx.reporter
+            .info("Run nvim_switcher switch VERSION to start using an imported version");
+
+        Ok(())
+    })();
+
+    let _ = fs::remove_dir_all(&temp);
+
+    result
+}
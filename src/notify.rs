@@ -0,0 +1,20 @@
+// desktop notifications through notify-send, which talks to the notification daemon over dbus
+use std::process::{Command, Stdio};
+
+// show a notification with a summary and a body
+pub fn send(summary: &str, body: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let status = Command::new("notify-send")
+        .arg("--app-name=nvim_switcher")
+        .arg(summary)
+        .arg(body)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(_) => Err("Failed to show a desktop notification".into()),
+        Err(_) => Err("notify-send is needed to show desktop notifications".into()),
+    }
+}
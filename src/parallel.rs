@@ -0,0 +1,240 @@
+// download a large archive over several connections at once, each fetching a range of it
+// a single connection rarely fills a link with a high latency, the parts together do
+use std::{
+    fs::File,
+    io::Read,
+    os::unix::fs::FileExt,
+    path::Path,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    thread,
+    time::Duration,
+};
+
+use reqwest::{
+    blocking::{Client, Request},
+    header::RANGE,
+    StatusCode,
+};
+
+use crate::{
+    context::Context,
+    http::{self, Failure},
+    progress::Progress,
+    throttle::Throttle,
+    BUFFER_SIZE,
+};
+
+// the number of connections used when not configured
+pub static DEFAULT_CONNECTIONS: usize = 4;
+
+// the smallest part worth a connection of its own, smaller files are downloaded over one
+static MIN_PART_SIZE: u64 = 1024 * 1024;
+
+// how often the progress of the parts is reported
+static POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// the size of the file at the url, none when it is not worth splitting or the server cannot send parts
+pub fn size(ctx: &Context, client: &Client, url: &str) -> Option<u64> {
+    if ctx.connections < 2 {
+        return None;
+    }
+
+    let response = http::send(ctx, http::authorize(ctx, client.head(url), url)).ok()?;
+    let ranges = response
+        .headers()
+        .get("Accept-Ranges")
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.eq_ignore_ascii_case("bytes"));
+
+    // a head response has no body, its length is that of the file
+    let size = response
+        .headers()
+        .get("Content-Length")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())?;
+
+    (response.status().is_success() && ranges && size >= 2 * MIN_PART_SIZE).then_some(size)
+}
+
+// what the parts of a download share
+struct Shared {
+    file: File,
+    // the bytes downloaded since the progress was last reported
+    done: AtomicU64,
+    // whether a part failed, which ends the others
+    failed: AtomicBool,
+    throttle: Throttle,
+    // the context stays on the main thread, the parts classify their failures like http::send with these
+    limits: http::Limits,
+    retries: u32,
+}
+
+// fetch the rest of a part from the offset into its place in the file, counting the bytes as they arrive
+// the offset moves with every write, so an attempt after a failure resumes where this one stopped
+fn attempt(
+    client: &Client,
+    request: &Request,
+    (offset, end): (&mut u64, u64),
+    shared: &Shared,
+) -> Result<(), Failure> {
+    let mut request = request
+        .try_clone()
+        .ok_or_else(|| Failure::permanent("invalid url"))?;
+    let range = format!("bytes={}-{}", offset, end)
+        .parse()
+        .map_err(|_| Failure::permanent("invalid range"))?;
+    request.headers_mut().insert(RANGE, range);
+
+    let mut response = http::classify(&shared.limits, client.execute(request))?;
+
+    // a server ignoring the range sends the whole file, which does not fit the part
+    let range = response
+        .headers()
+        .get("Content-Range")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    if response.status() != StatusCode::PARTIAL_CONTENT
+        || !range.starts_with(&format!("bytes {}-{}/", offset, end))
+    {
+        return Err(Failure::permanent(format!(
+            "the server responded to a part with {}",
+            response.status()
+        )));
+    }
+
+    let mut buffer = vec![0; BUFFER_SIZE];
+    while !shared.failed.load(Ordering::Relaxed) {
+        let read = match response.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(error) if http::timed_out(&error) => {
+                return Err(Failure::retryable(format!(
+                    "a part stalled for {}s",
+                    shared.limits.read_timeout.as_secs()
+                )))
+            }
+            Err(_) => return Err(Failure::retryable("a part was interrupted")),
+        };
+
+        shared
+            .file
+            .write_all_at(&buffer[..read], *offset)
+            .map_err(|_| Failure::permanent("the version could not be stored"))?;
+        *offset += read as u64;
+        shared.done.fetch_add(read as u64, Ordering::Relaxed);
+        shared.throttle.wait(read as u64);
+    }
+
+    if !shared.failed.load(Ordering::Relaxed) && *offset != end + 1 {
+        return Err(Failure::retryable("a part ended early"));
+    }
+
+    Ok(())
+}
+
+// fetch one part, retried like http::retry until it succeeds, fails permanently or the retries run out
+// the part stops early once another failed, the download is lost either way and the failure is
+// that of the other part
+fn part(
+    client: &Client,
+    request: &Request,
+    (start, end): (u64, u64),
+    shared: &Shared,
+) -> Result<(), String> {
+    let mut offset = start;
+    let mut delay = http::RETRY_DELAY;
+    let mut retry = 0;
+
+    loop {
+        let failure = match attempt(client, request, (&mut offset, end), shared) {
+            Ok(()) => return Ok(()),
+            Err(failure) => failure,
+        };
+
+        if !failure.retryable || retry >= shared.retries || shared.failed.load(Ordering::Relaxed) {
+            return Err(failure.message);
+        }
+
+        thread::sleep(http::backoff(delay));
+        delay *= 2;
+        retry += 1;
+    }
+}
+
+// download the file of the given size to the path, split over the configured number of connections
+// a part failing fails the whole download, the caller falls back to a single connection
+pub fn fetch(
+    ctx: &Context,
+    client: &Client,
+    url: &str,
+    path: &Path,
+    size: u64,
+) -> Result<(), String> {
+    let count = ctx.connections.min((size / MIN_PART_SIZE) as usize).max(1) as u64;
+    let length = size.div_ceil(count);
+
+    // the requests are built up front, the context cannot be shared with the threads
+    // every attempt of a part sends a copy of its request with the range still missing
+    let mut parts = Vec::new();
+    for index in 0..count {
+        let start = index * length;
+        let request = http::authorize(ctx, client.get(url), url)
+            .build()
+            .map_err(|_| "invalid url".to_string())?;
+
+        parts.push((request, (start, (start + length).min(size) - 1)));
+    }
+
+    // every part writes to its own place in the file
+    let file = File::create(path).map_err(|_| "the version could not be stored".to_string())?;
+    file.set_len(size)
+        .map_err(|_| "the version could not be stored".to_string())?;
+
+    ctx.reporter
+        .info(&format!("Downloading over {} connections", count));
+
+    let shared = Shared {
+        file,
+        done: AtomicU64::new(0),
+        failed: AtomicBool::new(false),
+        throttle: Throttle::new(ctx.limit_rate),
+        limits: http::Limits::of(ctx),
+        retries: ctx.retries,
+    };
+    let mut progress = Progress::new(ctx, 0, Some(size));
+    let results = thread::scope(|scope| {
+        let handles: Vec<_> = parts
+            .into_iter()
+            .map(|(request, range)| {
+                let shared = &shared;
+                scope.spawn(move || {
+                    let result = part(client, &request, range, shared);
+                    if result.is_err() {
+                        shared.failed.store(true, Ordering::Relaxed);
+                    }
+
+                    result
+                })
+            })
+            .collect();
+
+        // the reporter stays on this thread, it follows the count of the parts
+        while !handles.iter().all(|handle| handle.is_finished()) {
+            thread::sleep(POLL_INTERVAL);
+            progress.advance(shared.done.swap(0, Ordering::Relaxed));
+        }
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Err("a part failed".to_string()))
+            })
+            .collect::<Vec<_>>()
+    });
+    drop(progress);
+
+    results.into_iter().collect()
+}
@@ -0,0 +1,272 @@
+// all the locations nvim_switcher reads from and writes to
+// the cache only holds files that can be downloaded or extracted again,
+// anything the user would lose by clearing it lives in the data directory
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{context::Context, version};
+
+// get the directory the files of a version are cached in and its tag
+// forks are kept apart in a directory of their own, e.g. repos/owner/name
+fn namespace(version: &str) -> Result<(PathBuf, &str), Box<dyn std::error::Error>> {
+    let cache_dir = cache_dir()?;
+
+    match version::split_repo(version) {
+        (Some(repo), tag) => {
+            let dir = cache_dir.join("repos").join(repo);
+            ensure_dir(&dir)?;
+
+            Ok((dir, tag))
+        }
+        (None, tag) => Ok((cache_dir.to_path_buf(), tag)),
+    }
+}
+
+// get the path to the cached archive of the version
+pub fn archive(version: &str) -> Result<Box<Path>, Box<dyn std::error::Error>> {
+    // get the directory of the repository
+    let (dir, tag) = namespace(version)?;
+
+    // create the file path
+    let path = dir.join(format!("nvim-{}.tar.gz", tag));
+
+    // create the path
+    Ok(Path::new(&path).into())
+}
+
+// get the path to the file recording which upload of a version the cached archive is
+// only tags that are replaced upstream, like nightly, have one
+pub fn revision(version: &str) -> Result<Box<Path>, Box<dyn std::error::Error>> {
+    let (dir, tag) = namespace(version)?;
+
+    Ok(dir.join(format!("nvim-{}.revision", tag)).into())
+}
+
+// get the directory a version is extracted to when it is run without switching to it
+pub fn version_dir(version: &str) -> Result<Box<Path>, Box<dyn std::error::Error>> {
+    let (dir, tag) = namespace(version)?;
+
+    Ok(dir.join("versions").join(tag).into())
+}
+
+// get the path to the tags of the releases seen last time they were fetched, used to complete versions
+pub fn release_tags() -> Result<Box<Path>, Box<dyn std::error::Error>> {
+    Ok(cache_dir()?.join("releases").into())
+}
+
+// get the path to the last response of the api to a url, reused while github reports it unchanged
+pub fn api_response(url: &str) -> Result<Box<Path>, Box<dyn std::error::Error>> {
+    let dir = cache_dir()?.join("api");
+    ensure_dir(&dir)?;
+
+    // the url is only made fit for a file name, it stays readable for anyone looking at the cache
+    let name: String = url
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' => c,
+            _ => '_',
+        })
+        .collect();
+
+    Ok(dir.join(name).into())
+}
+
+// get the versions that have an archive in the cache, including those of forks
+pub fn cached_versions() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let cache_dir = cache_dir()?;
+
+    // the cache itself holds neovim, every repos/owner/name directory a fork
+    let mut dirs = vec![(None, cache_dir.to_path_buf())];
+    for owner in fs::read_dir(cache_dir.join("repos"))
+        .into_iter()
+        .flatten()
+        .flatten()
+    {
+        for name in fs::read_dir(owner.path()).into_iter().flatten().flatten() {
+            let repo = format!(
+                "{}/{}",
+                owner.file_name().to_string_lossy(),
+                name.file_name().to_string_lossy()
+            );
+            dirs.push((Some(repo), name.path()));
+        }
+    }
+
+    let mut versions = Vec::new();
+
+    // loop over the files in the cache
+    for (repo, dir) in dirs {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+
+            // archives are named nvim-VERSION.tar.gz
+            let name = entry.file_name();
+            let tag = name
+                .to_str()
+                .and_then(|name| name.strip_prefix("nvim-"))
+                .and_then(|name| name.strip_suffix(".tar.gz"));
+
+            if let Some(tag) = tag {
+                versions.push(version::qualify(repo.as_deref(), tag));
+            }
+        }
+    }
+
+    // the order of the directory entries is arbitrary
+    versions.sort_by(|a, b| version::compare(a, b));
+
+    Ok(versions)
+}
+
+// get the disk space used by a file or directory and everything in it, symlinks are not followed
+pub fn size(path: &Path) -> u64 {
+    let metadata = match path.symlink_metadata() {
+        Ok(metadata) => metadata,
+        Err(_) => return 0,
+    };
+
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| size(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+// get the home directory
+pub fn home_dir() -> Result<String, Box<dyn std::error::Error>> {
+    match env::var("HOME") {
+        Ok(home) if !home.is_empty() => Ok(home),
+        _ => Err("Could not determine the home directory, is HOME set?".into()),
+    }
+}
+
+// create the directory if it does not exist yet
+// another process creating it at the same time is not an error
+pub fn ensure_dir(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    match fs::create_dir_all(path) {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == io::ErrorKind::AlreadyExists && path.is_dir() => Ok(()),
+        Err(error) => {
+            Err(format!("Failed to create directory {}: {}", path.display(), error).into())
+        }
+    }
+}
+
+// get the cache directory
+pub fn cache_dir() -> Result<Box<Path>, Box<dyn std::error::Error>> {
+    // get the cache directory from the env var
+    // with a backup to the home directory
+    let mut dir = match env::var("XDG_CACHE_HOME") {
+        Ok(dir) if !dir.is_empty() => dir,
+        _ => home_dir()? + "/.cache",
+    };
+
+    // add our directory to the cache
+    dir += "/nvim_switcher";
+
+    // create the path
+    let path = Path::new(&dir);
+
+    // create the directory
+    ensure_dir(path)?;
+
+    Ok(path.into())
+}
+
+// get the data directory, holding state that should survive clearing the cache
+// e.g. aliases, the default version, history and manifests
+pub fn data_dir() -> Result<Box<Path>, Box<dyn std::error::Error>> {
+    // get the data directory from the env var
+    // with a backup to the home directory
+    let mut dir = match env::var("XDG_DATA_HOME") {
+        Ok(dir) if !dir.is_empty() => dir,
+        _ => home_dir()? + "/.local/share",
+    };
+
+    // add our directory to the data
+    dir += "/nvim_switcher";
+
+    // create the path
+    let path = Path::new(&dir);
+
+    // create the directory
+    ensure_dir(path)?;
+
+    Ok(path.into())
+}
+
+// get the config directory, nothing is ever written to it
+pub fn config_dir() -> Result<Box<Path>, Box<dyn std::error::Error>> {
+    // get the config directory from the env var
+    // with a backup to the home directory
+    let dir = match env::var("XDG_CONFIG_HOME") {
+        Ok(dir) if !dir.is_empty() => dir,
+        _ => home_dir()? + "/.config",
+    };
+
+    Ok(Path::new(&dir).join("nvim_switcher").into())
+}
+
+// get the directory the symlinks are placed in
+pub fn link_dir() -> Result<Box<Path>, Box<dyn std::error::Error>> {
+    Ok(Path::new(&(home_dir()? + "/.local")).into())
+}
+
+// get the output dir of the current version
+// this is the install dir when given, otherwise a directory in the cache
+pub fn output_dir(ctx: &Context) -> Result<Box<Path>, Box<dyn std::error::Error>> {
+    // format the output directory
+    let path = match &ctx.install_dir {
+        Some(dir) => dir.clone(),
+        None => cache_dir()?.join("current"),
+    };
+
+    // create the directory
+    ensure_dir(&path)?;
+
+    Ok(path.into())
+}
+
+// the file marking an install dir as extracted by nvim_switcher
+static OUTPUT_MARKER: &str = ".nvim_switcher";
+
+// the top level of an extracted release, an install dir extracted before the marker holds nothing else
+static RELEASE_DIRS: [&str; 3] = ["bin", "lib", "share"];
+
+// make sure the output dir may be removed, the one in the cache always may
+// an install dir must be empty or extracted by nvim_switcher, so a mistyped --install-dir,
+// e.g. /usr/local instead of /usr/local/nvim, is refused instead of wiped
+pub fn check_output_dir(ctx: &Context, dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let owned = ctx.install_dir.is_none()
+        || dir.join(OUTPUT_MARKER).exists()
+        || fs::read_dir(dir).map_or(true, |entries| {
+            entries
+                .flatten()
+                .all(|entry| RELEASE_DIRS.iter().any(|name| entry.file_name() == *name))
+        });
+    if !owned {
+        return Err(format!(
+            "Refusing to remove {}, it holds files nvim_switcher did not extract, use an empty or new directory as the install dir",
+            dir.display()
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+// mark the output dir as extracted by nvim_switcher, see check_output_dir
+pub fn mark_output_dir(dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    fs::write(dir.join(OUTPUT_MARKER), "")?;
+
+    Ok(())
+}
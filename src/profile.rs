@@ -0,0 +1,84 @@
+// profiles pair a version with an NVIM_APPNAME, so a config can be tried against a version in isolation
+use crate::{
+    context::Context,
+    requirement,
+    state::{self, Profile},
+    version,
+};
+
+// create or replace a profile, the appname defaults to the name of the profile
+// the version is stored as given and resolved when the profile is run
+pub fn add(
+    ctx: &Context,
+    name: &str,
+    version: &str,
+    appname: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // a profile is run in place of a version, so it may not look like one
+    if !version::is_valid_alias(name) {
+        return Err(format!(
+            "Invalid profile: {} (use letters, digits, - and _, and not a version)",
+            name
+        )
+        .into());
+    }
+
+    // nvim looks for the config in ~/.config/NVIM_APPNAME
+    let appname = appname.unwrap_or(name).trim();
+    if appname.is_empty()
+        || appname.starts_with('.')
+        || appname.contains(|c: char| c == '/' || c.is_whitespace())
+    {
+        return Err(format!("Invalid appname: {}", appname).into());
+    }
+
+    // refuse versions that will never resolve
+    let version = version.trim();
+    if requirement::is_requirement(version) {
+        requirement::Requirement::parse(version)?;
+    } else if version::partial(version).is_none() {
+        version::normalize(version)?;
+    }
+
+    state::set_profile(Profile {
+        name: name.to_string(),
+        version: version.to_string(),
+        appname: appname.to_string(),
+    })?;
+
+    ctx.reporter.success(&format!(
+        "Profile {} runs {} with NVIM_APPNAME={}",
+        name, version, appname
+    ));
+
+    Ok(())
+}
+
+// remove a profile, its config is left alone
+pub fn remove(ctx: &Context, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if state::remove_profile(name)? {
+        ctx.reporter.success(&format!("Removed profile {}", name));
+    } else {
+        ctx.reporter.info(&format!("There is no profile {}", name));
+    }
+
+    Ok(())
+}
+
+// list the profiles
+pub fn list(ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    let profiles = state::profiles()?;
+
+    if profiles.is_empty() {
+        ctx.reporter.info("No profiles defined");
+    }
+
+    for profile in profiles {
+        ctx.reporter.output(&format!(
+            "{} -> {} (NVIM_APPNAME={})",
+            profile.name, profile.version, profile.appname
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,161 @@
+// show how far a download got, so a slow link does not look like a hang
+// a terminal gets a bar redrawn in place, anything else a line every few seconds
+use std::{
+    io::{self, IsTerminal},
+    time::{Duration, Instant},
+};
+
+use crate::{context::Context, format_size};
+
+// the number of characters the bar is wide
+static BAR_WIDTH: usize = 30;
+
+// how often the bar is redrawn, more often only costs time
+static REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+// how often a line is logged when the output is not a terminal
+static LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+pub struct Progress<'a> {
+    ctx: &'a Context,
+    // the size of the file once complete, when the server told
+    total: Option<u64>,
+    // the bytes there are so far, including those of an earlier attempt that was resumed
+    done: u64,
+    // the bytes downloaded by this attempt, the speed is measured on them alone
+    downloaded: u64,
+    started: Instant,
+    reported: Instant,
+    terminal: bool,
+}
+
+impl<'a> Progress<'a> {
+    pub fn new(ctx: &'a Context, done: u64, total: Option<u64>) -> Self {
+        let now = Instant::now();
+
+        Progress {
+            ctx,
+            total,
+            done,
+            downloaded: 0,
+            started: now,
+            reported: now,
+            terminal: io::stdout().is_terminal(),
+        }
+    }
+
+    // count the bytes just written, reporting when it is time to
+    pub fn advance(&mut self, bytes: u64) {
+        self.done += bytes;
+        self.downloaded += bytes;
+
+        let interval = match self.terminal {
+            true => REDRAW_INTERVAL,
+            false => LOG_INTERVAL,
+        };
+        if self.reported.elapsed() < interval {
+            return;
+        }
+        self.reported = Instant::now();
+
+        match self.terminal {
+            true => self.ctx.reporter.progress(&self.bar()),
+            false => self.ctx.reporter.info(&self.line()),
+        }
+    }
+
+    // the bytes per second of this attempt
+    fn speed(&self) -> u64 {
+        let elapsed = self.started.elapsed().as_secs_f64();
+
+        match elapsed > 0.0 {
+            true => (self.downloaded as f64 / elapsed) as u64,
+            false => 0,
+        }
+    }
+
+    // the seconds left at the current speed, none while it is not known
+    fn remaining(&self) -> Option<u64> {
+        let total = self.total?;
+        let speed = self.speed();
+
+        (speed > 0).then(|| total.saturating_sub(self.done) / speed)
+    }
+
+    // the part that is done, from 0 to 1
+    fn fraction(&self) -> Option<f64> {
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| (self.done as f64 / total as f64).min(1.0))
+    }
+
+    // e.g. [#############>                ]  45%  12.3 MiB / 27.1 MiB  3.2 MiB/s  ETA 5s
+    fn bar(&self) -> String {
+        let speed = format!("{}/s", format_size(self.speed()));
+
+        match (self.fraction(), self.total) {
+            (Some(fraction), Some(total)) => {
+                let filled = (fraction * BAR_WIDTH as f64) as usize;
+                let bar = match filled {
+                    filled if filled >= BAR_WIDTH => "#".repeat(BAR_WIDTH),
+                    filled => format!(
+                        "{}>{}",
+                        "#".repeat(filled),
+                        " ".repeat(BAR_WIDTH - filled - 1)
+                    ),
+                };
+
+                format!(
+                    "[{}] {:>3.0}%  {} / {}  {}  ETA {}",
+                    bar,
+                    fraction * 100.0,
+                    format_size(self.done),
+                    format_size(total),
+                    speed,
+                    self.remaining()
+                        .map(format_seconds)
+                        .unwrap_or_else(|| "-".to_string())
+                )
+            }
+            // without a size only what arrived so far can be shown
+            _ => format!("{}  {}", format_size(self.done), speed),
+        }
+    }
+
+    // e.g. Downloaded 12.3 MiB of 27.1 MiB (45%) at 3.2 MiB/s, 5s left
+    fn line(&self) -> String {
+        let speed = format_size(self.speed());
+
+        match (self.fraction(), self.total) {
+            (Some(fraction), Some(total)) => format!(
+                "Downloaded {} of {} ({:.0}%) at {}/s{}",
+                format_size(self.done),
+                format_size(total),
+                fraction * 100.0,
+                speed,
+                self.remaining()
+                    .map(|remaining| format!(", {} left", format_seconds(remaining)))
+                    .unwrap_or_default()
+            ),
+            _ => format!("Downloaded {} at {}/s", format_size(self.done), speed),
+        }
+    }
+}
+
+// the bar is cleared once the download ends in any way, so it does not run into what is reported next
+impl Drop for Progress<'_> {
+    fn drop(&mut self) {
+        if self.terminal {
+            self.ctx.reporter.progress("");
+        }
+    }
+}
+
+// e.g. 5s, 2m 5s or 1h 2m
+fn format_seconds(seconds: u64) -> String {
+    match seconds {
+        0..=59 => format!("{}s", seconds),
+        60..=3599 => format!("{}m {}s", seconds / 60, seconds % 60),
+        _ => format!("{}h {}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
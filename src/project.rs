@@ -0,0 +1,41 @@
+// per project versions, read from a .nvim-version file like rustup and nvm do
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+// the name of the file holding the version of a project
+static VERSION_FILE: &str = ".nvim-version";
+
+// find the version file in the current directory or the closest parent that has one
+// returns the file and the version it holds
+pub fn version() -> Result<Option<(PathBuf, String)>, Box<dyn std::error::Error>> {
+    let cwd = env::current_dir()?;
+
+    for dir in cwd.ancestors() {
+        let file = dir.join(VERSION_FILE);
+
+        if file.is_file() {
+            return Ok(Some((file.clone(), read(&file)?)));
+        }
+    }
+
+    Ok(None)
+}
+
+// read the version from the file, the first line that is not empty or a # comment
+fn read(file: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let content = match fs::read_to_string(file) {
+        Ok(content) => content,
+        Err(_) => return Err(format!("Failed to read {}", file.display()).into()),
+    };
+
+    match content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+    {
+        Some(version) => Ok(version.to_string()),
+        None => Err(format!("{} does not contain a version", file.display()).into()),
+    }
+}
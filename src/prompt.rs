@@ -0,0 +1,289 @@
+use std::{
+    env,
+    io::{self, BufRead, IsTerminal, Read, Write},
+    process::{Command, Stdio},
+};
+
+use crate::context::Context;
+
+// ask the user to confirm an action
+// --yes answers every question, --no-input and a missing terminal fail instead of asking
+pub fn confirm(ctx: &Context, question: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    // the user already agreed to everything
+    if ctx.yes {
+        return Ok(true);
+    }
+
+    // we are not allowed to, or cannot, ask
+    if ctx.no_input || !io::stdin().is_terminal() {
+        return Err(format!("{} Pass --yes to confirm without a prompt", question).into());
+    }
+
+    // ask the question
+    print!("{} [y/N] ", question);
+    io::stdout().flush()?;
+
+    // read the answer
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes" | "Yes" | "YES"))
+}
+
+// ask the user a question and return the answer
+// unlike a confirmation this cannot be answered by --yes
+pub fn ask(ctx: &Context, question: &str) -> Result<String, Box<dyn std::error::Error>> {
+    // we are not allowed to, or cannot, ask
+    if ctx.no_input || !io::stdin().is_terminal() {
+        return Err(format!("{} Cannot prompt without a terminal", question).into());
+    }
+
+    // ask the question
+    print!("{} ", question);
+    io::stdout().flush()?;
+
+    // read the answer
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer)? == 0 {
+        return Err("No answer given".into());
+    }
+
+    Ok(answer.trim().to_string())
+}
+
+// the number of matches shown at once by the picker
+static PICKER_ROWS: usize = 10;
+
+// puts the terminal in a mode that passes every key press on right away, and restores it when dropped
+pub struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    pub fn enable() -> Result<RawMode, Box<dyn std::error::Error>> {
+        // stty works on the terminal it gets as stdin
+        let saved = Command::new("stty")
+            .arg("-g")
+            .stdin(Stdio::inherit())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .ok_or("Failed to read the terminal settings")?;
+
+        // reads wait a tenth of a second at most, so a lone escape can be told apart from an arrow key
+        let status = Command::new("stty")
+            .args(["-icanon", "-echo", "-isig", "min", "0", "time", "1"])
+            .stdin(Stdio::inherit())
+            .status();
+        if !status.is_ok_and(|status| status.success()) {
+            return Err("Failed to change the terminal settings".into());
+        }
+
+        Ok(RawMode { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = Command::new("stty")
+            .arg(&self.saved)
+            .stdin(Stdio::inherit())
+            .status();
+    }
+}
+
+// a key press the picker and the dashboard react to
+pub enum Key {
+    Char(char),
+    Backspace,
+    Up,
+    Down,
+    Enter,
+    Cancel,
+}
+
+// wait for the next key press, unknown keys are skipped
+pub fn read_key(stdin: &mut io::Stdin) -> Result<Key, Box<dyn std::error::Error>> {
+    // read a byte, none when the wait timed out
+    let mut next = || -> Result<Option<u8>, Box<dyn std::error::Error>> {
+        let mut byte = [0];
+        Ok(match stdin.read(&mut byte)? {
+            0 => None,
+            _ => Some(byte[0]),
+        })
+    };
+
+    loop {
+        let byte = match next()? {
+            Some(byte) => byte,
+            None => continue,
+        };
+
+        match byte {
+            b'\r' | b'\n' => return Ok(Key::Enter),
+            0x7f | 0x08 => return Ok(Key::Backspace),
+            // ctrl-p and ctrl-n
+            0x10 => return Ok(Key::Up),
+            0x0e => return Ok(Key::Down),
+            // ctrl-c and ctrl-g
+            0x03 | 0x07 => return Ok(Key::Cancel),
+            // arrow keys are sent as escape [ A or escape [ B, anything else cancels
+            0x1b => {
+                return Ok(match (next()?, next()?) {
+                    (Some(b'['), Some(b'A')) => Key::Up,
+                    (Some(b'['), Some(b'B')) => Key::Down,
+                    _ => Key::Cancel,
+                })
+            }
+            byte if byte.is_ascii_graphic() => return Ok(Key::Char(byte as char)),
+            _ => {}
+        }
+    }
+}
+
+// does the item contain the characters of the query in order, e.g. 010 matches v0.10.1
+// a lower score is a better match, the characters lying closer together and earlier on
+fn fuzzy_score(query: &str, item: &str) -> Option<(usize, usize)> {
+    let item: Vec<char> = item.to_lowercase().chars().collect();
+
+    let mut start = None;
+    let mut position = 0;
+    for wanted in query.to_lowercase().chars() {
+        let found = position + item[position..].iter().position(|c| *c == wanted)?;
+
+        start.get_or_insert(found);
+        position = found + 1;
+    }
+
+    let start = start.unwrap_or(0);
+
+    Some((position.saturating_sub(start), start))
+}
+
+// let the user pick one of the items by typing part of it, the arrow keys move the selection
+pub fn pick(
+    ctx: &Context,
+    question: &str,
+    items: &[String],
+) -> Result<String, Box<dyn std::error::Error>> {
+    // we are not allowed to, or cannot, ask
+    if ctx.no_input || !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(format!("{} Cannot prompt without a terminal", question).into());
+    }
+
+    if items.is_empty() {
+        return Err(format!("{} There is nothing to choose from", question).into());
+    }
+
+    let raw = RawMode::enable()?;
+    let mut stdin = io::stdin();
+    let mut stdout = io::stdout();
+
+    let mut query = String::new();
+    let mut selected = 0;
+    let mut drawn = 0;
+
+    let picked = loop {
+        // the matching items, best first, items that match equally well keep their order
+        let mut matches: Vec<(usize, usize, &String)> = items
+            .iter()
+            .filter_map(|item| fuzzy_score(&query, item).map(|(span, start)| (span, start, item)))
+            .collect();
+        matches.sort_by_key(|(span, start, _)| (*span, *start));
+        let matches: Vec<&String> = matches.into_iter().map(|(_, _, item)| item).collect();
+
+        selected = selected.min(matches.len().saturating_sub(1));
+
+        // redraw the matches above the line the query is typed on
+        if drawn > 0 {
+            write!(stdout, "\x1b[{}A", drawn)?;
+        }
+        write!(stdout, "\r\x1b[J")?;
+
+        let shown = &matches[..matches.len().min(PICKER_ROWS)];
+        for (index, item) in shown.iter().enumerate() {
+            if index == selected {
+                writeln!(stdout, "\x1b[7m> {}\x1b[0m", item)?;
+            } else {
+                writeln!(stdout, "  {}", item)?;
+            }
+        }
+        drawn = shown.len();
+
+        write!(stdout, "{} {}", question, query)?;
+        stdout.flush()?;
+
+        match read_key(&mut stdin)? {
+            Key::Char(c) => {
+                query.push(c);
+                selected = 0;
+            }
+            Key::Backspace => {
+                query.pop();
+                selected = 0;
+            }
+            Key::Up => selected = selected.saturating_sub(1),
+            Key::Down => selected = (selected + 1).min(shown.len().saturating_sub(1)),
+            Key::Enter => {
+                if let Some(item) = shown.get(selected) {
+                    break Some(item.to_string());
+                }
+            }
+            Key::Cancel => break None,
+        }
+    };
+
+    // leave nothing of the picker behind
+    if drawn > 0 {
+        write!(stdout, "\x1b[{}A", drawn)?;
+    }
+    write!(stdout, "\r\x1b[J")?;
+    stdout.flush()?;
+    drop(raw);
+
+    picked.ok_or_else(|| "Cancelled".into())
+}
+
+// show a long text through the pager of the user, $PAGER or less
+// without a terminal, or when the pager cannot be started, the text is simply printed
+pub fn page(ctx: &Context, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // paging only makes sense when a person is reading along
+    if ctx.no_input || !io::stdout().is_terminal() || !io::stdin().is_terminal() {
+        ctx.reporter.output(text);
+
+        return Ok(());
+    }
+
+    // start the pager, less quits right away when the text fits on the screen
+    let pager = env::var("PAGER")
+        .ok()
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or_else(|| "less".to_string());
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(&pager)
+        .env(
+            "LESS",
+            env::var("LESS").unwrap_or_else(|_| "FRX".to_string()),
+        )
+        .stdin(Stdio::piped())
+        .spawn();
+
+    let mut child = match child {
+        Ok(child) => child,
+        Err(_) => {
+            ctx.reporter.output(text);
+
+            return Ok(());
+        }
+    };
+
+    // a pager that is closed early stops reading, that is not an error
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    child.wait()?;
+
+    Ok(())
+}
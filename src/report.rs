@@ -0,0 +1,198 @@
+// everything the commands tell the user goes through a reporter,
+// so the output can be silenced, captured or formatted in one place
+use std::{
+    cell::RefCell,
+    io::{self, IsTerminal, Write},
+    rc::Rc,
+    time::SystemTime,
+};
+
+use crate::timestamp;
+
+pub trait Reporter {
+    // progress and other details of what is happening
+    fn info(&self, message: &str);
+
+    // the outcome of a command
+    fn success(&self, message: &str);
+
+    // something went wrong but the command carries on
+    fn warn(&self, message: &str);
+
+    // the command failed
+    fn error(&self, message: &str);
+
+    // the data the command was asked for, e.g. a list or a url, this is never silenced
+    fn output(&self, message: &str);
+
+    // how far a download got, redrawn in place on a terminal, an empty message clears it
+    fn progress(&self, message: &str);
+}
+
+// the default reporter, messages go to stdout and problems to stderr
+pub struct StdoutReporter;
+
+impl Reporter for StdoutReporter {
+    fn info(&self, message: &str) {
+        println!("{}", message);
+    }
+
+    fn success(&self, message: &str) {
+        println!("{}", message);
+    }
+
+    fn warn(&self, message: &str) {
+        eprintln!("Warning: {}", message);
+    }
+
+    fn error(&self, message: &str) {
+        eprintln!("{}", message);
+    }
+
+    fn output(&self, message: &str) {
+        println!("{}", message);
+    }
+
+    fn progress(&self, message: &str) {
+        // a line redrawn in place would fill a log with every step
+        if !io::stdout().is_terminal() {
+            return;
+        }
+
+        // go back to the start of the line and clear what was drawn before
+        print!("\r\x1b[K{}", message);
+        let _ = io::stdout().flush();
+    }
+}
+
+// only reports problems and the requested data
+pub struct QuietReporter;
+
+impl Reporter for QuietReporter {
+    fn info(&self, _: &str) {}
+
+    fn success(&self, _: &str) {}
+
+    fn warn(&self, message: &str) {
+        eprintln!("Warning: {}", message);
+    }
+
+    fn error(&self, message: &str) {
+        eprintln!("{}", message);
+    }
+
+    fn output(&self, message: &str) {
+        println!("{}", message);
+    }
+
+    fn progress(&self, _: &str) {}
+}
+
+// prefixes every message with the current time, for long running commands whose output is kept as a log
+pub struct TimestampReporter {
+    pub inner: Rc<dyn Reporter>,
+}
+
+impl TimestampReporter {
+    fn stamp(&self, message: &str) -> String {
+        format!(
+            "[{}] {}",
+            timestamp::format(timestamp::seconds(SystemTime::now())),
+            message
+        )
+    }
+}
+
+impl Reporter for TimestampReporter {
+    fn info(&self, message: &str) {
+        self.inner.info(&self.stamp(message));
+    }
+
+    fn success(&self, message: &str) {
+        self.inner.success(&self.stamp(message));
+    }
+
+    fn warn(&self, message: &str) {
+        self.inner.warn(&self.stamp(message));
+    }
+
+    fn error(&self, message: &str) {
+        self.inner.error(&self.stamp(message));
+    }
+
+    fn output(&self, message: &str) {
+        self.inner.output(&self.stamp(message));
+    }
+
+    // the line is replaced soon after, it does not need a time
+    fn progress(&self, message: &str) {
+        self.inner.progress(message);
+    }
+}
+
+// the kind of a captured message
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Level {
+    Info,
+    Success,
+    Warn,
+    Error,
+    Output,
+}
+
+// keeps the messages instead of printing them, so they can be inspected or replayed later
+#[derive(Default)]
+pub struct CaptureReporter {
+    messages: RefCell<Vec<(Level, String)>>,
+}
+
+impl CaptureReporter {
+    fn push(&self, level: Level, message: &str) {
+        self.messages
+            .borrow_mut()
+            .push((level, message.to_string()));
+    }
+
+    // get the captured messages
+    pub fn messages(&self) -> Vec<(Level, String)> {
+        self.messages.borrow().clone()
+    }
+
+    // pass the captured messages on to another reporter
+    pub fn replay(&self, reporter: &dyn Reporter) {
+        for (level, message) in self.messages() {
+            match level {
+                Level::Info => reporter.info(&message),
+                Level::Success => reporter.success(&message),
+                Level::Warn => reporter.warn(&message),
+                Level::Error => reporter.error(&message),
+                Level::Output => reporter.output(&message),
+            }
+        }
+    }
+}
+
+impl Reporter for CaptureReporter {
+    fn info(&self, message: &str) {
+        self.push(Level::Info, message);
+    }
+
+    fn success(&self, message: &str) {
+        self.push(Level::Success, message);
+    }
+
+    fn warn(&self, message: &str) {
+        self.push(Level::Warn, message);
+    }
+
+    fn error(&self, message: &str) {
+        self.push(Level::Error, message);
+    }
+
+    fn output(&self, message: &str) {
+        self.push(Level::Output, message);
+    }
+
+    // only what was reported in the end is kept
+    fn progress(&self, _: &str) {}
+}
@@ -0,0 +1,226 @@
+// version requirements in the semver style, e.g. `>=0.9, <0.11` or `^0.10`
+// a requirement is a comma separated list of comparators that all have to match
+
+// the operators a comparator can start with, longest first so `>=` is not read as `>`
+static OPERATORS: [&str; 7] = [">=", "<=", ">", "<", "=", "^", "~"];
+
+// a single comparison against a complete version
+enum Bound {
+    AtLeast([u64; 3]),
+    Above([u64; 3]),
+    Below([u64; 3]),
+    AtMost([u64; 3]),
+}
+
+// a parsed requirement
+pub struct Requirement {
+    bounds: Vec<Bound>,
+}
+
+// is the text meant as a requirement rather than a single version
+pub fn is_requirement(text: &str) -> bool {
+    let text = text.trim();
+
+    text.contains(',') || OPERATORS.iter().any(|operator| text.starts_with(operator))
+}
+
+impl Requirement {
+    // parse a requirement, a comparator without operator is treated like `^` as cargo does
+    pub fn parse(text: &str) -> Result<Requirement, Box<dyn std::error::Error>> {
+        let invalid = || format!("Invalid version requirement: {}", text.trim());
+
+        let mut bounds = Vec::new();
+
+        for comparator in text.split(',') {
+            let comparator = comparator.trim();
+
+            // split off the operator
+            let operator = OPERATORS
+                .iter()
+                .find(|operator| comparator.starts_with(*operator))
+                .copied()
+                .unwrap_or("^");
+            let version = comparator
+                .strip_prefix(operator)
+                .unwrap_or(comparator)
+                .trim();
+
+            // the version may leave out its minor and patch parts
+            let parts = parts(version).ok_or_else(invalid)?;
+            let lowest = pad(&parts);
+
+            match operator {
+                ">=" => bounds.push(Bound::AtLeast(lowest)),
+                "<" => bounds.push(Bound::Below(lowest)),
+                ">" if parts.len() == 3 => bounds.push(Bound::Above(lowest)),
+                ">" => bounds.push(Bound::AtLeast(bump(&parts))),
+                "<=" if parts.len() == 3 => bounds.push(Bound::AtMost(lowest)),
+                "<=" => bounds.push(Bound::Below(bump(&parts))),
+                "=" if parts.len() == 3 => {
+                    bounds.push(Bound::AtLeast(lowest));
+                    bounds.push(Bound::AtMost(lowest));
+                }
+                "=" => {
+                    bounds.push(Bound::AtLeast(lowest));
+                    bounds.push(Bound::Below(bump(&parts)));
+                }
+                // allow patch changes, or minor changes when only the major part is given
+                "~" => {
+                    bounds.push(Bound::AtLeast(lowest));
+                    bounds.push(Bound::Below(bump(&parts[..parts.len().min(2)])));
+                }
+                // allow changes that leave the first non-zero part alone
+                _ => {
+                    let significant = match parts.iter().position(|part| *part != 0) {
+                        Some(index) => index + 1,
+                        None => parts.len(),
+                    };
+
+                    bounds.push(Bound::AtLeast(lowest));
+                    bounds.push(Bound::Below(bump(&parts[..significant])));
+                }
+            }
+        }
+
+        Ok(Requirement { bounds })
+    }
+
+    // does the version satisfy every comparator
+    pub fn matches(&self, version: &[u64; 3]) -> bool {
+        self.bounds.iter().all(|bound| match bound {
+            Bound::AtLeast(bound) => version >= bound,
+            Bound::Above(bound) => version > bound,
+            Bound::Below(bound) => version < bound,
+            Bound::AtMost(bound) => version <= bound,
+        })
+    }
+}
+
+// get the one to three numeric parts of a version, the v prefix is optional
+fn parts(version: &str) -> Option<Vec<u64>> {
+    let version = version.trim_start_matches(['v', 'V']);
+
+    let parts = version
+        .split('.')
+        .map(|part| {
+            part.parse::<u64>()
+                .ok()
+                .filter(|_| part.chars().all(|c| c.is_ascii_digit()))
+        })
+        .collect::<Option<Vec<u64>>>()?;
+
+    match parts.len() {
+        1..=3 => Some(parts),
+        _ => None,
+    }
+}
+
+// fill in the missing parts with zeroes, e.g. 0.10 becomes 0.10.0
+fn pad(parts: &[u64]) -> [u64; 3] {
+    let mut version = [0; 3];
+    version[..parts.len()].copy_from_slice(parts);
+
+    version
+}
+
+// get the first version after every version starting with the parts, e.g. 0.10 becomes 0.11.0
+// the largest part stays as it is, no release comes after it anyway
+fn bump(parts: &[u64]) -> [u64; 3] {
+    let mut version = pad(parts);
+    version[parts.len() - 1] = version[parts.len() - 1].saturating_add(1);
+
+    version
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version;
+
+    // the versions out of the list that satisfy the requirement
+    fn matching(requirement: &str, versions: &[&'static str]) -> Vec<&'static str> {
+        let requirement = Requirement::parse(requirement).unwrap();
+
+        versions
+            .iter()
+            .copied()
+            .filter(|tag| version::number(tag).is_some_and(|number| requirement.matches(&number)))
+            .collect()
+    }
+
+    static RELEASES: [&str; 9] = [
+        "v0.8.3", "v0.9.0", "v0.9.5", "v0.10.0", "v0.10.4", "v0.11.0", "v1.0.0", "v1.2.3",
+        "nightly",
+    ];
+
+    #[test]
+    fn comma_separated_comparators_all_have_to_match() {
+        assert_eq!(
+            matching(">=0.9, <0.11", &RELEASES),
+            ["v0.9.0", "v0.9.5", "v0.10.0", "v0.10.4"]
+        );
+        assert_eq!(
+            matching(">0.9.0,<=0.10", &RELEASES),
+            ["v0.9.5", "v0.10.0", "v0.10.4"]
+        );
+        assert_eq!(
+            matching(">0.10", &RELEASES),
+            ["v0.11.0", "v1.0.0", "v1.2.3"]
+        );
+        assert_eq!(matching("=0.10", &RELEASES), ["v0.10.0", "v0.10.4"]);
+        assert_eq!(matching("=v0.10.4", &RELEASES), ["v0.10.4"]);
+    }
+
+    #[test]
+    fn caret_and_tilde_allow_compatible_changes() {
+        assert_eq!(matching("^0.10", &RELEASES), ["v0.10.0", "v0.10.4"]);
+        assert_eq!(matching("^1", &RELEASES), ["v1.0.0", "v1.2.3"]);
+        assert_eq!(matching("^1.0.1", &RELEASES), ["v1.2.3"]);
+        assert_eq!(matching("~0.9.1", &RELEASES), ["v0.9.5"]);
+        assert_eq!(matching("~1", &RELEASES), ["v1.0.0", "v1.2.3"]);
+
+        // without an operator like cargo
+        assert_eq!(matching("0.9", &RELEASES), ["v0.9.0", "v0.9.5"]);
+    }
+
+    #[test]
+    fn nightly_never_matches() {
+        for requirement in [">=0", "<99", "^0", "~0", ">=0.0.0, <99"] {
+            assert!(!matching(requirement, &RELEASES).contains(&"nightly"));
+        }
+
+        // a prerelease tag has no number to compare
+        assert!(matching(">=0", &["v0.11.0-dev", "stable"]).is_empty());
+    }
+
+    #[test]
+    fn invalid_requirements_are_refused() {
+        for text in [
+            "",
+            ">=",
+            ">=0.9,",
+            ">=0.9, <",
+            ">=nightly",
+            "^0.x",
+            ">=0.9.1.2",
+            "=>0.9",
+            ">=-1",
+            ">= 0.9 0.10",
+        ] {
+            assert!(Requirement::parse(text).is_err(), "{:?}", text);
+        }
+
+        // a bound past the largest part does not overflow
+        assert!(Requirement::parse(&format!("<={}", u64::MAX)).is_ok());
+    }
+
+    #[test]
+    fn requirements_are_told_apart_from_versions() {
+        for text in [">=0.9", "<0.11", "^0.10", "~0.9.1", "=0.10.0", "0.9, 0.10"] {
+            assert!(is_requirement(text), "{}", text);
+        }
+        for text in ["0.10", "v0.10.0", "stable", "nightly"] {
+            assert!(!is_requirement(text), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use std::{
+    env,
+    fs::{self, File},
+    os::unix::fs::PermissionsExt,
+    path::Path,
+    process::Command,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{checksum, context::Context, http, paths::cache_dir, sha256, version::number};
+
+// the repository nvim_switcher itself is released from
+static SELF_REPO_URL: &str = "https://github.com/Damian-Wantzing/nvim_switcher/releases/";
+
+// the name of the binary attached to the releases, builds for a platform add -linux-ARCH
+// older releases only have the plain name
+static SELF_ASSET: &str = "nvim_switcher";
+
+// how long the result of an update check is reused before checking again
+static CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+// get the tag of the latest release of nvim_switcher
+fn latest_release(ctx: &Context) -> Result<String, Box<dyn std::error::Error>> {
+    // github redirects the latest release to the page of its tag
+    let client = http::client(ctx)?;
+    let url = SELF_REPO_URL.to_string() + "latest";
+    let response = match http::retry(ctx, "Failed to check for updates", || {
+        http::send(ctx, http::authorize(ctx, client.get(&url), &url))
+    }) {
+        Ok(response) => response,
+        Err(_) => {
+            return Err("Failed to check for updates".into());
+        }
+    };
+
+    // the final url looks like .../releases/tag/v0.2.0
+    match response.url().path().rsplit_once("/tag/") {
+        Some((_, tag)) => Ok(tag.to_string()),
+        None => Err("No release of nvim_switcher found".into()),
+    }
+}
+
+// is the given tag newer than the running binary
+fn is_newer(tag: &str) -> bool {
+    match (number(tag), number(env!("CARGO_PKG_VERSION"))) {
+        (Some(latest), Some(running)) => latest > running,
+        _ => false,
+    }
+}
+
+// get the names of the assets that may hold the binary for this machine, best first
+fn asset_names() -> Vec<String> {
+    vec![
+        format!("{}-linux-{}", SELF_ASSET, env::consts::ARCH),
+        SELF_ASSET.to_string(),
+    ]
+}
+
+// make sure the downloaded binary runs on this machine and is the release we asked for
+// this runs the binary, so it is only done once its checksum matched
+fn verify(binary: &Path, tag: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // a truncated download or a build for another platform fails to run at all
+    let output = match Command::new(binary).arg("--version").output() {
+        Ok(output) if output.status.success() => output,
+        _ => return Err("The downloaded binary does not run on this machine".into()),
+    };
+
+    // the version is printed as `nvim_switcher 0.2.0`
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let reported = stdout.split_whitespace().last().and_then(number);
+    if reported.is_none() || reported != number(tag) {
+        return Err(format!(
+            "The downloaded binary reports version {} instead of {}",
+            stdout.trim(),
+            tag
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+// print a notice when a newer nvim_switcher is available
+// the result is cached so the network is consulted at most once a day
+pub fn check_update(ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    // the file holding the time of the last check and the tag that was found
+    let cache = cache_dir()?.join("self_update");
+
+    // get the current time in seconds
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
+    // reuse the previous result when it is recent enough
+    let cached = fs::read_to_string(&cache).ok().and_then(|content| {
+        let (checked, tag) = content.trim().split_once(' ')?;
+        let checked: u64 = checked.parse().ok()?;
+
+        (now.saturating_sub(checked) < CHECK_INTERVAL.as_secs()).then(|| tag.to_string())
+    });
+
+    // check the network when there is no recent result
+    let tag = match cached {
+        Some(tag) => tag,
+        None => {
+            let tag = latest_release(ctx)?;
+
+            // remember the result, failing to do so only means we check again next time
+            let _ = fs::write(&cache, format!("{} {}", now, tag));
+
+            tag
+        }
+    };
+
+    if is_newer(&tag) {
+        ctx.reporter.info(&format!(
+            "A new version of nvim_switcher is available: {} (running v{}), run `nvim_switcher self-update` to install it",
+            tag,
+            env!("CARGO_PKG_VERSION")
+        ));
+    }
+
+    Ok(())
+}
+
+// update the running binary to the latest release
+pub fn self_update(ctx: &Context, check: bool) -> Result<(), Box<dyn std::error::Error>> {
+    // get the latest release
+    let tag = latest_release(ctx)?;
+
+    // is there anything to update
+    if !is_newer(&tag) {
+        ctx.reporter.success(&format!(
+            "nvim_switcher v{} is up to date",
+            env!("CARGO_PKG_VERSION")
+        ));
+
+        return Ok(());
+    }
+
+    ctx.reporter.info(&format!(
+        "A new version of nvim_switcher is available: {} (running v{})",
+        tag,
+        env!("CARGO_PKG_VERSION")
+    ));
+
+    // only report the update
+    if check {
+        return Ok(());
+    }
+
+    // attempt to download the binary for this platform, falling back to the plain one
+    let client = http::client(ctx)?;
+    let mut response = None;
+    for name in asset_names() {
+        let url = SELF_REPO_URL.to_string() + "download/" + &tag + "/" + &name;
+
+        ctx.reporter
+            .info(&format!("Pulling nvim_switcher {} from {}", tag, url));
+
+        let found = http::retry(ctx, &format!("Failed to download {}", url), || {
+            http::send(ctx, http::authorize(ctx, client.get(&url), &url))
+        });
+        if let Ok(found) = found {
+            if found.status().is_success() {
+                response = Some((found, url));
+                break;
+            }
+        }
+    }
+
+    let (mut response, url) = match response {
+        Some(response) => response,
+        None => {
+            return Err("Failed to download update".into());
+        }
+    };
+
+    // the new binary is written next to the running one, so it can be renamed over it
+    let exe = env::current_exe()?;
+    let new = exe.with_extension("new");
+
+    // create the file
+    let mut file = match File::create(&new) {
+        Ok(file) => file,
+        Err(_) => {
+            return Err("Failed to store update".into());
+        }
+    };
+
+    // write the binary
+    if response.copy_to(&mut file).is_err() {
+        let _ = fs::remove_file(&new);
+
+        return Err("Failed to store update".into());
+    }
+
+    // the binary is compared to the checksum published next to it before it is ever run
+    drop(file);
+    let expected = match checksum::published(ctx, &url, "sha256") {
+        Some(expected) => expected,
+        None => {
+            let _ = fs::remove_file(&new);
+
+            return Err(format!(
+                "No checksum published for nvim_switcher {}, refusing to install it",
+                tag
+            )
+            .into());
+        }
+    };
+    let actual = match sha256::file(&new) {
+        Ok(actual) => actual,
+        Err(_) => {
+            let _ = fs::remove_file(&new);
+
+            return Err("Failed to store update".into());
+        }
+    };
+    if actual != expected {
+        let _ = fs::remove_file(&new);
+
+        return Err(format!(
+            "The checksum of {} does not match, expected {} but got {}",
+            url, expected, actual
+        )
+        .into());
+    }
+
+    // make it executable and check it before it replaces the running binary
+    fs::set_permissions(&new, fs::Permissions::from_mode(0o755))?;
+    if let Err(error) = verify(&new, &tag) {
+        let _ = fs::remove_file(&new);
+
+        return Err(error);
+    }
+
+    // renaming over the running binary is atomic, it keeps running from the old file
+    if fs::rename(&new, &exe).is_err() {
+        let _ = fs::remove_file(&new);
+
+        return Err(format!("Failed to replace {}", exe.display()).into());
+    }
+
+    ctx.reporter
+        .success(&format!("Updated nvim_switcher to {}", tag));
+
+    Ok(())
+}
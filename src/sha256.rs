@@ -0,0 +1,201 @@
+// the sha-256 digest, as published next to the release archives
+use std::{
+    fs::File,
+    io::{self, BufReader, Read},
+    path::Path,
+};
+
+use crate::BUFFER_SIZE;
+
+// the first 32 bits of the fractional parts of the cube roots of the first 64 primes
+static K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+// the first 32 bits of the fractional parts of the square roots of the first 8 primes
+static INITIAL: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+// a digest being computed over data fed to it in parts
+pub struct Sha256 {
+    state: [u32; 8],
+    // the start of a block that is not complete yet
+    pending: Vec<u8>,
+    // the number of bytes fed so far
+    length: u64,
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha256 {
+    pub fn new() -> Self {
+        Sha256 {
+            state: INITIAL,
+            pending: Vec::with_capacity(64),
+            length: 0,
+        }
+    }
+
+    // feed the next part of the data
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.length += data.len() as u64;
+
+        // complete the pending block first
+        if !self.pending.is_empty() {
+            let missing = (64 - self.pending.len()).min(data.len());
+            self.pending.extend_from_slice(&data[..missing]);
+            data = &data[missing..];
+
+            if self.pending.len() < 64 {
+                return;
+            }
+
+            let block = std::mem::take(&mut self.pending);
+            self.compress(&block);
+        }
+
+        let mut blocks = data.chunks_exact(64);
+        for block in &mut blocks {
+            self.compress(block);
+        }
+        self.pending.extend_from_slice(blocks.remainder());
+    }
+
+    // get the digest of everything fed, as lowercase hex
+    pub fn finish(mut self) -> String {
+        let bits = self.length.wrapping_mul(8);
+
+        // a single one bit, zeros up to 8 bytes short of a block and the length in bits
+        let mut padding = vec![0x80];
+        padding.resize((119 - self.length % 64) as usize % 64 + 1, 0);
+        padding.extend_from_slice(&bits.to_be_bytes());
+        self.update(&padding);
+
+        self.state
+            .iter()
+            .map(|word| format!("{:08x}", word))
+            .collect()
+    }
+
+    // mix a block of 64 bytes into the state
+    fn compress(&mut self, block: &[u8]) {
+        let mut words = [0u32; 64];
+        for (index, word) in block.chunks_exact(4).enumerate() {
+            words[index] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for index in 16..64 {
+            let s0 = words[index - 15].rotate_right(7)
+                ^ words[index - 15].rotate_right(18)
+                ^ (words[index - 15] >> 3);
+            let s1 = words[index - 2].rotate_right(17)
+                ^ words[index - 2].rotate_right(19)
+                ^ (words[index - 2] >> 10);
+            words[index] = words[index - 16]
+                .wrapping_add(s0)
+                .wrapping_add(words[index - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for index in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let choice = (e & f) ^ (!e & g);
+            let temp1 = h
+                .wrapping_add(s1)
+                .wrapping_add(choice)
+                .wrapping_add(K[index])
+                .wrapping_add(words[index]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let majority = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(majority);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+
+        for (state, value) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+}
+
+// get the digest of a file, read through a fixed size buffer
+pub fn file(path: &Path) -> io::Result<String> {
+    let mut file = BufReader::with_capacity(BUFFER_SIZE, File::open(path)?);
+    let mut buffer = vec![0; BUFFER_SIZE];
+    let mut digest = Sha256::new();
+
+    loop {
+        match file.read(&mut buffer)? {
+            0 => return Ok(digest.finish()),
+            read => digest.update(&buffer[..read]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the digest of the data fed in parts of the given size
+    fn chunked(data: &[u8], size: usize) -> String {
+        let mut digest = Sha256::new();
+        for chunk in data.chunks(size) {
+            digest.update(chunk);
+        }
+
+        digest.finish()
+    }
+
+    #[test]
+    fn known_answers() {
+        // from the sha-256 examples of nist
+        for (data, expected) in [
+            (
+                "",
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                "abc",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+        ] {
+            let mut digest = Sha256::new();
+            digest.update(data.as_bytes());
+            assert_eq!(digest.finish(), expected, "{:?}", data);
+        }
+    }
+
+    #[test]
+    fn parts_give_the_digest_of_the_whole() {
+        // long enough to span several blocks and end in a partial one
+        let data: Vec<u8> = (0..1000u32).map(|index| (index * 7 + 3) as u8).collect();
+        let whole = chunked(&data, data.len());
+
+        for size in [1, 63, 64, 65] {
+            assert_eq!(chunked(&data, size), whole, "parts of {} bytes", size);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+// snippets for the shells nvim_switcher integrates with
+use std::{env, path::Path};
+
+use clap::ValueEnum;
+
+// the supported shells
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+impl Shell {
+    // guess the shell from $SHELL, falling back to bash
+    pub fn detect() -> Shell {
+        let shell = env::var("SHELL").unwrap_or_default();
+        let name = Path::new(&shell)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+
+        match name {
+            "zsh" => Shell::Zsh,
+            "fish" => Shell::Fish,
+            "pwsh" | "powershell" => Shell::Powershell,
+            _ => Shell::Bash,
+        }
+    }
+}
+
+// quote a value for the shell
+pub fn quote(shell: Shell, value: &str) -> String {
+    match shell {
+        Shell::Powershell => format!("'{}'", value.replace('\'', "''")),
+        Shell::Fish => format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'")),
+        _ => format!("'{}'", value.replace('\'', r"'\''")),
+    }
+}
+
+// the lines that put the linked binaries first on the PATH
+// nvim finds its runtime next to the binary the links point to, so VIMRUNTIME is left alone
+pub fn env(shell: Shell, bin: &str) -> String {
+    let bin = quote(shell, bin);
+
+    match shell {
+        Shell::Bash | Shell::Zsh => format!("export PATH={}:\"$PATH\"\n", bin),
+        Shell::Fish => format!("set -gx PATH {} $PATH\n", bin),
+        Shell::Powershell => format!(
+            "$env:PATH = {} + [IO.Path]::PathSeparator + $env:PATH\n",
+            bin
+        ),
+    }
+}
+
+// a snippet that switches to the version of the project whenever the directory changes
+// the switch only runs when the closest .nvim-version file is a different one than before
+pub fn hook(shell: Shell, exe: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let exe = quote(shell, exe);
+
+    let snippet = match shell {
+        // bash has no directory change hook, so the check runs before every prompt
+        Shell::Bash | Shell::Zsh => {
+            let function = format!(
+                r#"_nvim_switcher_hook() {{
+  local dir="$PWD" file=""
+  while [ -n "$dir" ]; do
+    if [ -f "$dir/.nvim-version" ]; then
+      file="$dir/.nvim-version"
+      break
+    fi
+    [ "$dir" = "/" ] && break
+    dir="$(dirname "$dir")"
+  done
+  if [ "$file" != "$_NVIM_SWITCHER_FILE" ]; then
+    _NVIM_SWITCHER_FILE="$file"
+    if [ -n "$file" ]; then
+      (cd "$(dirname "$file")" && {exe} --quiet switch)
+    fi
+  fi
+}}
+"#
+            );
+
+            match shell {
+                Shell::Bash => {
+                    function
+                        + r#"case ";$PROMPT_COMMAND;" in
+  *";_nvim_switcher_hook;"*) ;;
+  *) PROMPT_COMMAND="_nvim_switcher_hook${PROMPT_COMMAND:+;$PROMPT_COMMAND}" ;;
+esac
+"#
+                }
+                _ => {
+                    function
+                        + "autoload -U add-zsh-hook\n\
+                       add-zsh-hook chpwd _nvim_switcher_hook\n\
+                       _nvim_switcher_hook\n"
+                }
+            }
+        }
+        Shell::Fish => format!(
+            r#"function __nvim_switcher_hook --on-variable PWD
+    set -l dir $PWD
+    set -l file ""
+    while test -n "$dir"
+        if test -f "$dir/.nvim-version"
+            set file "$dir/.nvim-version"
+            break
+        end
+        test "$dir" = "/"; and break
+        set dir (dirname "$dir")
+    end
+    if test "$file" != "$__nvim_switcher_file"
+        set -g __nvim_switcher_file "$file"
+        if test -n "$file"
+            pushd (dirname "$file")
+            {exe} --quiet switch
+            popd
+        end
+    end
+end
+__nvim_switcher_hook
+"#
+        ),
+        Shell::Powershell => return Err("The hook is not available for PowerShell".into()),
+    };
+
+    Ok(snippet)
+}
@@ -0,0 +1,129 @@
+// a script in place of the nvim symlink that picks the version every time nvim starts
+// NVIM_SWITCHER_VERSION wins, then the closest .nvim-version file, then the installed version
+// so selecting a version per shell or per project needs no switch
+use std::{
+    env, fs,
+    os::unix::{fs::PermissionsExt, process::CommandExt},
+    path::PathBuf,
+    process::Command,
+};
+
+use crate::{
+    context::Context,
+    extracted,
+    links::{self, Link},
+    paths, project,
+    shell::{self, Shell},
+};
+
+// the variable selecting the version for a single shell or command
+static VERSION_VAR: &str = "NVIM_SWITCHER_VERSION";
+
+// the line telling the shim apart from anything else at the path
+static MARKER: &str = "# nvim_switcher shim";
+
+// get where the shim is placed, where the nvim symlink would otherwise be
+pub fn path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(paths::link_dir()?.join("bin/nvim"))
+}
+
+// is the shim in place of the nvim symlink
+pub fn is_installed() -> bool {
+    path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .is_some_and(|content| content.lines().nth(1) == Some(MARKER))
+}
+
+// replace the nvim symlink with the shim
+pub fn install(ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    let exe = env::current_exe()?;
+
+    // the output of nvim may be piped, so only errors are printed
+    let mut command = vec![
+        shell::quote(Shell::Bash, &exe.display().to_string()),
+        "--quiet".to_string(),
+    ];
+    if let Some(dir) = &ctx.install_dir {
+        command.push("--install-dir".to_string());
+        command.push(shell::quote(Shell::Bash, &dir.display().to_string()));
+    }
+
+    let script = format!(
+        "#!/bin/sh\n{}\nexec {} shim exec -- \"$@\"\n",
+        MARKER,
+        command.join(" ")
+    );
+
+    let path = path()?;
+    if let Some(parent) = path.parent() {
+        paths::ensure_dir(parent)?;
+    }
+
+    // a symlink would otherwise be followed and the installed binary overwritten
+    if path.symlink_metadata().is_ok() {
+        fs::remove_file(&path)?;
+    }
+
+    if fs::write(&path, script).is_err() {
+        return Err(format!("Failed to write {}", path.display()).into());
+    }
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+
+    ctx.reporter.success(&format!(
+        "Installed the shim at {}, nvim now follows {} and .nvim-version files",
+        path.display(),
+        VERSION_VAR
+    ));
+
+    Ok(())
+}
+
+// put the nvim symlink back in place of the shim
+pub fn uninstall(ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    if !is_installed() {
+        ctx.reporter.info("The shim is not installed");
+
+        return Ok(());
+    }
+
+    let path = path()?;
+    fs::remove_file(&path)?;
+
+    // the symlink only makes sense when a version is installed
+    let nvim = paths::output_dir(ctx)?.join("bin/nvim");
+    if nvim.exists() {
+        links::create(&Link {
+            link: path.clone(),
+            target: nvim,
+        })?;
+    }
+
+    ctx.reporter
+        .success(&format!("Removed the shim at {}", path.display()));
+
+    Ok(())
+}
+
+// run the selected version of nvim with the arguments, replacing this process
+pub fn exec(ctx: &Context, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let selected = match env::var(VERSION_VAR) {
+        Ok(version) if !version.trim().is_empty() => Some(version.trim().to_string()),
+        _ => project::version()?.map(|(_, version)| version),
+    };
+
+    // the installed version needs nothing extracted
+    let nvim = match selected {
+        Some(version) => extracted(ctx, &version)?.join("bin/nvim"),
+        None => paths::output_dir(ctx)?.join("bin/nvim"),
+    };
+
+    if !nvim.exists() {
+        return Err("No version installed, run nvim_switcher switch VERSION".into());
+    }
+
+    // exec only returns when nvim could not be started
+    let error = Command::new(&nvim).args(args).exec();
+
+    Err(format!("Failed to run {}: {}", nvim.display(), error).into())
+}
@@ -0,0 +1,164 @@
+// check downloaded archives against a signature made with a key the user trusts
+// neovim does not sign its releases, this is for mirrors and forks that do
+// minisign signatures are read from NAME.minisig and gpg signatures from NAME.asc, next to the archive
+use std::{
+    env, fs,
+    os::unix::fs::PermissionsExt,
+    path::Path,
+    process::{self, Command, Stdio},
+};
+
+use crate::{context::Context, http, paths};
+
+// how a signature turned out
+enum Outcome {
+    Verified,
+    // nothing could be verified, e.g. no signature was published
+    Unverified(String),
+    Invalid,
+}
+
+// run a command quietly, none when it is not installed
+fn status(command: &mut Command) -> Option<bool> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .ok()
+        .map(|status| status.success())
+}
+
+// get the signature published next to the archive, none when there is none
+// it is only a few hundred bytes, so it is kept in memory rather than downloaded like the archive
+fn signature(ctx: &Context, url: &str, extension: &str) -> Option<Vec<u8>> {
+    let url = format!("{}.{}", url, extension);
+    let client = http::client(ctx).ok()?;
+    let response = http::retry(ctx, &format!("Failed to download {}", url), || {
+        http::send(ctx, http::authorize(ctx, client.get(&url), &url))
+    })
+    .ok()?;
+
+    if !response.status().is_success() {
+        return None;
+    }
+
+    response.bytes().ok().map(|bytes| bytes.to_vec())
+}
+
+// verify with minisign against the public key itself, e.g. RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3
+fn minisign(ctx: &Context, key: &str, url: &str, archive: &Path) -> Outcome {
+    let signature = match signature(ctx, url, "minisig") {
+        Some(signature) => signature,
+        None => return Outcome::Unverified("no minisign signature was published".to_string()),
+    };
+
+    // minisign only reads the signature from a file
+    let path = env::temp_dir().join(format!("nvim_switcher-{}.minisig", process::id()));
+    if fs::write(&path, signature).is_err() {
+        return Outcome::Unverified("the signature could not be stored".to_string());
+    }
+
+    let verified = status(
+        Command::new("minisign")
+            .args(["-V", "-q", "-P", key, "-m"])
+            .arg(archive)
+            .arg("-x")
+            .arg(&path),
+    );
+    let _ = fs::remove_file(&path);
+
+    match verified {
+        Some(true) => Outcome::Verified,
+        Some(false) => Outcome::Invalid,
+        None => Outcome::Unverified("minisign is not installed".to_string()),
+    }
+}
+
+// verify with gpg against the keys in the file, imported into a keyring of their own
+// so the keys of the user are never trusted by accident
+fn gpg(ctx: &Context, key: &Path, url: &str, archive: &Path) -> Outcome {
+    let signature = match signature(ctx, url, "asc") {
+        Some(signature) => signature,
+        None => return Outcome::Unverified("no gpg signature was published".to_string()),
+    };
+
+    let home = env::temp_dir().join(format!("nvim_switcher-gpg-{}", process::id()));
+    let outcome = (|| {
+        // gpg refuses a home directory others can read
+        if paths::ensure_dir(&home).is_err()
+            || fs::set_permissions(&home, fs::Permissions::from_mode(0o700)).is_err()
+        {
+            return Outcome::Unverified("the keyring could not be created".to_string());
+        }
+
+        let gpg = || {
+            let mut command = Command::new("gpg");
+            command.arg("--homedir").arg(&home).arg("--batch");
+            command
+        };
+
+        match status(gpg().arg("--import").arg(key)) {
+            Some(true) => {}
+            Some(false) => {
+                return Outcome::Unverified(format!("{} could not be imported", key.display()))
+            }
+            None => return Outcome::Unverified("gpg is not installed".to_string()),
+        }
+
+        // the signature is kept in the keyring directory, which is removed with it
+        let path = home.join("signature.asc");
+        if fs::write(&path, &signature).is_err() {
+            return Outcome::Unverified("the signature could not be stored".to_string());
+        }
+
+        match status(gpg().arg("--verify").arg(&path).arg(archive)) {
+            Some(true) => Outcome::Verified,
+            _ => Outcome::Invalid,
+        }
+    })();
+
+    let _ = fs::remove_dir_all(&home);
+
+    outcome
+}
+
+// check the signature of the archive downloaded from the url with the configured key
+// a signature that does not match always fails, a missing one only with require_signed
+pub fn verify(
+    ctx: &Context,
+    version: &str,
+    url: &str,
+    archive: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let outcome = match (&ctx.minisign_key, &ctx.gpg_key) {
+        (Some(key), _) => minisign(ctx, key, url, archive),
+        (None, Some(key)) => gpg(ctx, key, url, archive),
+        (None, None) => Outcome::Unverified("no signing key is configured".to_string()),
+    };
+
+    match outcome {
+        Outcome::Verified => {
+            ctx.reporter
+                .info(&format!("Verified the signature of version {}", version));
+
+            Ok(())
+        }
+        Outcome::Invalid => Err(format!("The signature of {} does not match the key", url).into()),
+        Outcome::Unverified(reason) if ctx.require_signed => Err(format!(
+            "Refusing version {}, its signature could not be verified: {}",
+            version, reason
+        )
+        .into()),
+        // without a key there is nothing to warn about
+        Outcome::Unverified(_) if ctx.minisign_key.is_none() && ctx.gpg_key.is_none() => Ok(()),
+        Outcome::Unverified(reason) => {
+            ctx.reporter.warn(&format!(
+                "The signature of version {} could not be verified: {}",
+                version, reason
+            ));
+
+            Ok(())
+        }
+    }
+}
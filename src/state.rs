@@ -0,0 +1,350 @@
+// the small state files kept in the data directory
+use std::fs;
+
+use crate::paths;
+
+// read a state file as a list of lines, a missing file is an empty list
+fn read_lines(name: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    match fs::read_to_string(paths::data_dir()?.join(name)) {
+        Ok(content) => Ok(content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.to_string())
+            .collect()),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+// write a list of lines to a state file
+fn write_lines(name: &str, lines: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut content = lines.join("\n");
+    if !content.is_empty() {
+        content += "\n";
+    }
+
+    if fs::write(paths::data_dir()?.join(name), content).is_err() {
+        return Err(format!("Failed to write {}", name).into());
+    }
+
+    Ok(())
+}
+
+// get the pinned versions, these are never removed from the cache automatically
+pub fn pins() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    read_lines("pins")
+}
+
+// is the version pinned
+pub fn is_pinned(version: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    Ok(pins()?.iter().any(|pin| pin == version))
+}
+
+// pin the version, returns false when it already was
+pub fn pin(version: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut pins = pins()?;
+
+    if pins.iter().any(|pin| pin == version) {
+        return Ok(false);
+    }
+
+    pins.push(version.to_string());
+    pins.sort();
+    write_lines("pins", &pins)?;
+
+    Ok(true)
+}
+
+// unpin the version, returns false when it was not pinned
+pub fn unpin(version: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut pins = pins()?;
+    let count = pins.len();
+
+    pins.retain(|pin| pin != version);
+    if pins.len() == count {
+        return Ok(false);
+    }
+
+    write_lines("pins", &pins)?;
+
+    Ok(true)
+}
+
+// get the version that was active before the last switch
+pub fn previous() -> Result<Option<String>, Box<dyn std::error::Error>> {
+    Ok(read_lines("previous")?.into_iter().next())
+}
+
+// remember the version that was active before a switch
+pub fn set_previous(version: &str) -> Result<(), Box<dyn std::error::Error>> {
+    write_lines("previous", &[version.to_string()])
+}
+
+// get the newest release a desktop notification was shown for
+pub fn notified() -> Result<Option<String>, Box<dyn std::error::Error>> {
+    Ok(read_lines("notified")?.into_iter().next())
+}
+
+// remember a release was notified about, so it is only notified once
+pub fn set_notified(version: &str) -> Result<(), Box<dyn std::error::Error>> {
+    write_lines("notified", &[version.to_string()])
+}
+
+// get the version to restore when the install is lost, e.g. because the cache was cleared
+pub fn default_version() -> Result<Option<String>, Box<dyn std::error::Error>> {
+    Ok(read_lines("default")?.into_iter().next())
+}
+
+// set the version to restore, none removes it
+pub fn set_default_version(version: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let lines: Vec<String> = version
+        .into_iter()
+        .map(|version| version.to_string())
+        .collect();
+
+    write_lines("default", &lines)
+}
+
+// get the recorded download times as pairs of a version and seconds since the unix epoch
+fn downloads() -> Result<Vec<(String, u64)>, Box<dyn std::error::Error>> {
+    Ok(read_lines("downloads")?
+        .iter()
+        .filter_map(|line| {
+            let (version, seconds) = line.split_once(' ')?;
+
+            Some((version.to_string(), seconds.parse().ok()?))
+        })
+        .collect())
+}
+
+// write the download times
+fn write_downloads(downloads: &[(String, u64)]) -> Result<(), Box<dyn std::error::Error>> {
+    let lines: Vec<String> = downloads
+        .iter()
+        .map(|(version, seconds)| format!("{} {}", version, seconds))
+        .collect();
+
+    write_lines("downloads", &lines)
+}
+
+// get when the version was downloaded, if that was recorded
+pub fn downloaded_at(version: &str) -> Result<Option<u64>, Box<dyn std::error::Error>> {
+    Ok(downloads()?
+        .into_iter()
+        .find(|(downloaded, _)| downloaded == version)
+        .map(|(_, seconds)| seconds))
+}
+
+// record when the version was downloaded, replacing an earlier download
+pub fn record_download(version: &str, seconds: u64) -> Result<(), Box<dyn std::error::Error>> {
+    let mut downloads = downloads()?;
+
+    downloads.retain(|(downloaded, _)| downloaded != version);
+    downloads.push((version.to_string(), seconds));
+
+    write_downloads(&downloads)
+}
+
+// forget the download time of a removed version
+pub fn forget_download(version: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut downloads = downloads()?;
+
+    downloads.retain(|(downloaded, _)| downloaded != version);
+
+    write_downloads(&downloads)
+}
+
+// get the version nvim reported and the stamp of the binary it was reported for
+pub fn current() -> Result<Option<(String, String)>, Box<dyn std::error::Error>> {
+    let mut lines = read_lines("current")?.into_iter();
+
+    Ok(lines.next().zip(lines.next()))
+}
+
+// remember the version nvim reported for the binary with the stamp
+pub fn set_current(version: &str, stamp: &str) -> Result<(), Box<dyn std::error::Error>> {
+    write_lines("current", &[version.to_string(), stamp.to_string()])
+}
+
+// get the aliases as pairs of a name and the version it stands for
+pub fn aliases() -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    Ok(read_lines("aliases")?
+        .iter()
+        .filter_map(|line| {
+            let (name, version) = line.split_once(' ')?;
+
+            Some((name.to_string(), version.to_string()))
+        })
+        .collect())
+}
+
+// get the version an alias stands for
+pub fn alias(name: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    Ok(aliases()?
+        .into_iter()
+        .find(|(alias, _)| alias == name)
+        .map(|(_, version)| version))
+}
+
+// create or replace an alias
+pub fn set_alias(name: &str, version: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut aliases = aliases()?;
+
+    aliases.retain(|(alias, _)| alias != name);
+    aliases.push((name.to_string(), version.to_string()));
+    aliases.sort();
+
+    write_aliases(&aliases)
+}
+
+// remove an alias, returns false when there was none
+pub fn remove_alias(name: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut aliases = aliases()?;
+    let count = aliases.len();
+
+    aliases.retain(|(alias, _)| alias != name);
+    if aliases.len() == count {
+        return Ok(false);
+    }
+
+    write_aliases(&aliases)?;
+
+    Ok(true)
+}
+
+// write the aliases
+fn write_aliases(aliases: &[(String, String)]) -> Result<(), Box<dyn std::error::Error>> {
+    let lines: Vec<String> = aliases
+        .iter()
+        .map(|(name, version)| format!("{} {}", name, version))
+        .collect();
+
+    write_lines("aliases", &lines)
+}
+
+// a version paired with the NVIM_APPNAME it is run with
+pub struct Profile {
+    pub name: String,
+    pub version: String,
+    pub appname: String,
+}
+
+// get the profiles
+pub fn profiles() -> Result<Vec<Profile>, Box<dyn std::error::Error>> {
+    Ok(read_lines("profiles")?
+        .iter()
+        .filter_map(|line| {
+            // the version comes last, a requirement may contain spaces
+            let mut fields = line.splitn(3, ' ');
+
+            Some(Profile {
+                name: fields.next()?.to_string(),
+                appname: fields.next()?.to_string(),
+                version: fields.next()?.to_string(),
+            })
+        })
+        .collect())
+}
+
+// get a profile by its name
+pub fn profile(name: &str) -> Result<Option<Profile>, Box<dyn std::error::Error>> {
+    Ok(profiles()?.into_iter().find(|profile| profile.name == name))
+}
+
+// create or replace a profile
+pub fn set_profile(profile: Profile) -> Result<(), Box<dyn std::error::Error>> {
+    let mut profiles = profiles()?;
+
+    profiles.retain(|existing| existing.name != profile.name);
+    profiles.push(profile);
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+
+    write_profiles(&profiles)
+}
+
+// remove a profile, returns false when there was none
+pub fn remove_profile(name: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut profiles = profiles()?;
+    let count = profiles.len();
+
+    profiles.retain(|profile| profile.name != name);
+    if profiles.len() == count {
+        return Ok(false);
+    }
+
+    write_profiles(&profiles)?;
+
+    Ok(true)
+}
+
+// write the profiles
+fn write_profiles(profiles: &[Profile]) -> Result<(), Box<dyn std::error::Error>> {
+    let lines: Vec<String> = profiles
+        .iter()
+        .map(|profile| format!("{} {} {}", profile.name, profile.appname, profile.version))
+        .collect();
+
+    write_lines("profiles", &lines)
+}
+
+// get the channels linked next to the active version, e.g. stable and nightly
+pub fn channels() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    read_lines("channels")
+}
+
+// add a channel, returns false when it was already there
+pub fn add_channel(name: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut channels = channels()?;
+    if channels.iter().any(|channel| channel == name) {
+        return Ok(false);
+    }
+
+    channels.push(name.to_string());
+    channels.sort();
+    write_lines("channels", &channels)?;
+
+    Ok(true)
+}
+
+// remove a channel, returns false when there was none
+pub fn remove_channel(name: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut channels = channels()?;
+    let count = channels.len();
+
+    channels.retain(|channel| channel != name);
+    if channels.len() == count {
+        return Ok(false);
+    }
+
+    write_lines("channels", &channels)?;
+
+    Ok(true)
+}
+
+// the version the install is frozen at
+pub struct Lock {
+    pub version: String,
+    pub reason: Option<String>,
+}
+
+// get the lock of the install, none when it is not locked
+pub fn lock() -> Result<Option<Lock>, Box<dyn std::error::Error>> {
+    let mut lines = read_lines("lock")?.into_iter();
+
+    Ok(lines.next().map(|version| Lock {
+        version,
+        reason: lines.next(),
+    }))
+}
+
+// lock the install, none unlocks it
+pub fn set_lock(lock: Option<&Lock>) -> Result<(), Box<dyn std::error::Error>> {
+    let lines: Vec<String> = match lock {
+        Some(lock) => std::iter::once(&lock.version)
+            .chain(&lock.reason)
+            .cloned()
+            .collect(),
+        None => Vec::new(),
+    };
+
+    write_lines("lock", &lines)
+}
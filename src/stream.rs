@@ -0,0 +1,259 @@
+// download a version and extract it while it arrives, instead of storing the archive and extracting it after
+// this writes half as much and never needs room for the archive next to the extracted version
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use flate2::bufread::GzDecoder;
+use tar::Archive;
+
+use crate::{
+    asset::asset_urls, attestation, checksum, context::Context, http, paths, progress::Progress,
+    sha256::Sha256, signature, state, throttle::Throttle, timestamp, unpack, upstream_revision,
+    version, BUFFER_SIZE,
+};
+
+// passes the download on to the decoder, hashing it and copying it to the archive on the way
+struct Tee<'a, R> {
+    inner: R,
+    digest: Sha256,
+    copy: Option<BufWriter<File>>,
+    progress: Progress<'a>,
+    throttle: Throttle,
+}
+
+impl<R: Read> Read for Tee<'_, R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buffer)?;
+
+        self.digest.update(&buffer[..read]);
+        if let Some(copy) = &mut self.copy {
+            copy.write_all(&buffer[..read])?;
+        }
+        self.progress.advance(read as u64);
+        self.throttle.wait(read as u64);
+
+        Ok(read)
+    }
+}
+
+impl<R> Tee<'_, R> {
+    // the digest of everything read, once the copy is written out
+    fn finish(self) -> io::Result<String> {
+        let Tee {
+            digest,
+            copy,
+            progress,
+            ..
+        } = self;
+        drop(progress);
+
+        if let Some(mut copy) = copy {
+            copy.flush()?;
+        }
+
+        Ok(digest.finish())
+    }
+}
+
+// whether the version can be streamed, signatures and attestations are only checked on an archive that is kept
+pub fn available(ctx: &Context, version: &str) -> bool {
+    let verified = ctx.minisign_key.is_some() || ctx.gpg_key.is_some() || ctx.verify_attestation;
+
+    ctx.stream && (ctx.keep_archive || !verified) && !version::is_snapshot(version)
+}
+
+// stream the archive at the url into the dir, copying it to the given path on the way
+// returns the digest of the archive
+fn receive(
+    ctx: &Context,
+    url: &str,
+    copy: Option<&Path>,
+    dir: &Path,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let client = http::client(ctx)?;
+    let response = http::retry(ctx, &format!("Failed to download {}", url), || {
+        let response = http::send(ctx, http::authorize(ctx, client.get(url), url))?;
+        if !response.status().is_success() {
+            return Err(http::Failure::permanent(format!(
+                "the server responded with {}",
+                response.status()
+            )));
+        }
+
+        Ok(response)
+    })?;
+
+    let copy = match copy {
+        Some(path) => Some(BufWriter::with_capacity(BUFFER_SIZE, File::create(path)?)),
+        None => None,
+    };
+    let total = response.content_length();
+    let tee = Tee {
+        inner: response,
+        digest: Sha256::new(),
+        copy,
+        progress: Progress::new(ctx, 0, total),
+        throttle: Throttle::new(ctx.limit_rate),
+    };
+
+    // a mirror serving an error page fails on the gzip header
+    let mut tar = Archive::new(GzDecoder::new(BufReader::with_capacity(BUFFER_SIZE, tee)));
+    if let Err(error) = unpack(&mut tar, dir) {
+        return Err(format!("Failed to extract {}: {}", url, error).into());
+    }
+
+    // the end of the tar archive can come before the end of the download, the rest is part of the checksum
+    let mut decoder = tar.into_inner();
+    io::copy(&mut decoder, &mut io::sink())?;
+    io::copy(decoder.get_mut(), &mut io::sink())?;
+
+    Ok(decoder.into_inner().into_inner().finish()?)
+}
+
+// stream the archive at the url into the dir and verify it, keeping it at the archive path when given
+fn attempt(
+    ctx: &Context,
+    version: &str,
+    url: &str,
+    archive: Option<&Path>,
+    dir: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // the copy only becomes the archive once all of it arrived, like a download
+    let partial = archive.map(|path| {
+        let mut name = path.as_os_str().to_owned();
+        name.push(".partial");
+        PathBuf::from(name)
+    });
+
+    let stream = || -> Result<String, Box<dyn std::error::Error>> {
+        let received = receive(ctx, url, partial.as_deref(), dir);
+        if let (Some(partial), Some(archive)) = (&partial, archive) {
+            match &received {
+                Ok(_) => fs::rename(partial, archive)?,
+                Err(_) => {
+                    let _ = fs::remove_file(partial);
+                }
+            }
+        }
+
+        received
+    };
+
+    // a corrupted transfer rarely repeats, so a mismatching checksum gets one more download like
+    // it does without streaming, into the dir emptied of what the first one extracted
+    if let Err(error) = checksum::check(ctx, version, url, &stream()?) {
+        ctx.reporter.warn(&error.to_string());
+
+        fs::remove_dir_all(dir)?;
+        paths::ensure_dir(dir)?;
+        checksum::check(ctx, version, url, &stream()?)?;
+    }
+    if let Some(archive) = archive {
+        signature::verify(ctx, version, url, archive)?;
+        attestation::verify(ctx, version, archive)?;
+    }
+
+    Ok(())
+}
+
+// download the version and extract it into the dir as it arrives, replacing what the dir held
+// the version is extracted next to the dir first, so a download failing halfway leaves the dir alone
+pub fn install(ctx: &Context, version: &str, dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    if ctx.offline.get() {
+        return Err(format!(
+            "Version {} is not cached and cannot be downloaded offline",
+            version
+        )
+        .into());
+    }
+
+    let archive = paths::archive(version)?;
+    let kept = ctx.keep_archive.then_some(&*archive);
+    let partial = dir.with_extension("partial");
+
+    // try the mirrors in order until one of them serves the archive
+    let urls = asset_urls(ctx, version)?;
+    let mut served = None;
+    for url in &urls {
+        ctx.reporter.info(&format!(
+            "Streaming version {} of nvim from {}",
+            version, url
+        ));
+
+        let _ = fs::remove_dir_all(&partial);
+        paths::ensure_dir(&partial)?;
+
+        match attempt(ctx, version, url, kept, &partial) {
+            Ok(()) => {
+                served = Some(url.clone());
+
+                break;
+            }
+            Err(error) => {
+                let _ = fs::remove_dir_all(&partial);
+                if let Some(archive) = kept {
+                    let _ = fs::remove_file(archive);
+                }
+
+                ctx.reporter.warn(&error.to_string());
+            }
+        }
+    }
+
+    let url = match served {
+        Some(url) => url,
+        None => {
+            http::detect_offline(ctx);
+
+            return Err(match urls.len() {
+                1 => format!("Failed to download version {}", version),
+                count => format!(
+                    "Failed to download version {} from any of the {} mirrors",
+                    version, count
+                ),
+            }
+            .into());
+        }
+    };
+
+    // replace the version in the dir
+    if fs::remove_dir_all(dir).is_err() {
+        let _ = fs::remove_dir_all(&partial);
+
+        return Err("Failed to remove current version".into());
+    }
+    fs::rename(&partial, dir)?;
+
+    // a kept archive is cached like a download, see download
+    if kept.is_some() {
+        if state::record_download(version, timestamp::seconds(SystemTime::now())).is_err() {
+            ctx.reporter.warn(&format!(
+                "Failed to record the download time of version {}",
+                version
+            ));
+        }
+
+        if let Some(revision) = match version::split_repo(version).1 {
+            "nightly" => upstream_revision(ctx, version),
+            _ => None,
+        } {
+            if fs::write(paths::revision(version)?, revision).is_err() {
+                ctx.reporter.warn(&format!(
+                    "Failed to record the build of version {}",
+                    version
+                ));
+            }
+        }
+    }
+
+    ctx.reporter.success(&format!(
+        "Downloaded version {} of nvim from {}",
+        version, url
+    ));
+
+    Ok(())
+}
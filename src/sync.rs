@@ -0,0 +1,186 @@
+// bring the cache in line with a file declaring the wanted versions, pins, aliases and default
+// the same file is written by export, so the setup of one machine can be imported on another
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    active_version, alias, config::Config, context::Context, download, paths, prompt,
+    purge_version, report_reclaimed, state, switch, toml, version,
+};
+
+// the name of the file declaring the versions
+static FILE_NAME: &str = "nvim-switcher.toml";
+
+// the keys the file may hold besides the aliases section
+static KEYS: [&str; 4] = ["versions", "pins", "default", "active"];
+
+// get the file to sync from, the one in the current directory or else the one in the config directory
+fn find(file: Option<&Path>) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if let Some(file) = file {
+        return Ok(file.to_path_buf());
+    }
+
+    let local = env::current_dir()?.join(FILE_NAME);
+    if local.exists() {
+        return Ok(local);
+    }
+
+    Ok(paths::config_dir()?.join(FILE_NAME))
+}
+
+// download what the file declares, set its pins, aliases and default and switch to the default
+// cached versions the file does not mention are only removed when pruning
+pub fn sync(
+    ctx: &Context,
+    file: Option<&Path>,
+    prune: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = find(file)?;
+    if !file.exists() {
+        return Err(format!("{} not found", file.display()).into());
+    }
+
+    let config = Config::load_file(&file)?;
+
+    // a typo should not silently leave something out
+    if let Some(key) = config
+        .keys()
+        .into_iter()
+        .find(|key| !KEYS.contains(key) && !key.starts_with("aliases."))
+    {
+        return Err(format!("Unknown key {} in {}", key, file.display()).into());
+    }
+
+    ctx.reporter
+        .info(&format!("Syncing from {}", file.display()));
+
+    // the aliases come first, so the versions may refer to them
+    for key in config.keys() {
+        if let Some(name) = key.strip_prefix("aliases.") {
+            match config.string(key) {
+                Some(version) => alias(ctx, Some(name), Some(&version), false)?,
+                None => return Err(format!("Alias {} should be a string", name).into()),
+            }
+        }
+    }
+
+    // resolve every version that is asked for into its tag
+    let pins = config.strings("pins");
+    let default = config.string("default");
+    let active = config.string("active");
+
+    let mut wanted = Vec::new();
+    for requested in config
+        .strings("versions")
+        .unwrap_or_default()
+        .iter()
+        .chain(pins.iter().flatten())
+        .chain(default.iter())
+        .chain(active.iter())
+    {
+        let tag = version::resolve_version(ctx, requested)?;
+        if !wanted.contains(&tag) {
+            wanted.push(tag);
+        }
+    }
+
+    // download what is missing, nightly is checked for a newer build
+    for version in &wanted {
+        if !paths::archive(version)?.exists() || version == "nightly" {
+            download(ctx, version)?;
+        }
+    }
+
+    // the pins become exactly the ones listed, when the file lists any
+    if let Some(pins) = &pins {
+        let pins: Vec<String> = pins
+            .iter()
+            .map(|pin| version::resolve_version(ctx, pin))
+            .collect::<Result<_, _>>()?;
+
+        for pinned in state::pins()? {
+            if !pins.contains(&pinned) {
+                state::unpin(&pinned)?;
+                ctx.reporter.info(&format!("Unpinned version {}", pinned));
+            }
+        }
+
+        for pin in &pins {
+            if state::pin(pin)? {
+                ctx.reporter.info(&format!("Pinned version {}", pin));
+            }
+        }
+    }
+
+    // remember the default
+    if let Some(default) = &default {
+        state::set_default_version(Some(&version::resolve_version(ctx, default)?))?;
+    }
+
+    // switch to the active version, or else the default
+    if let Some(version) = active.as_ref().or(default.as_ref()) {
+        switch(ctx, &version::resolve_version(ctx, version)?)?;
+    }
+
+    // remove what the file does not mention, except the active version
+    if prune {
+        let active = active_version(ctx);
+        let unlisted: Vec<String> = paths::cached_versions()?
+            .into_iter()
+            .filter(|cached| !wanted.contains(cached) && active.as_ref() != Some(cached))
+            .collect();
+
+        if !unlisted.is_empty()
+            && prompt::confirm(ctx, &format!("Remove {}?", unlisted.join(", ")))?
+        {
+            let mut reclaimed = 0;
+            for version in &unlisted {
+                reclaimed += purge_version(ctx, version)?;
+            }
+
+            report_reclaimed(ctx, reclaimed);
+        }
+    }
+
+    ctx.reporter
+        .success(&format!("Synced with {}", file.display()));
+
+    Ok(())
+}
+
+// describe the cached versions, pins, aliases, default and active version in the format sync reads
+pub fn export(ctx: &Context) -> Result<String, Box<dyn std::error::Error>> {
+    let list = |items: &[String]| {
+        items
+            .iter()
+            .map(|item| toml::quote(item))
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+
+    let mut content = format!(
+        "versions = [{}]\npins = [{}]\n",
+        list(&paths::cached_versions()?),
+        list(&state::pins()?)
+    );
+
+    if let Some(default) = state::default_version()? {
+        content += &format!("default = {}\n", toml::quote(&default));
+    }
+    if let Some(active) = active_version(ctx) {
+        content += &format!("active = {}\n", toml::quote(&active));
+    }
+
+    // aliases are stored as given, they are resolved again on the other machine
+    let aliases = state::aliases()?;
+    if !aliases.is_empty() {
+        content += "\n[aliases]\n";
+        for (name, version) in aliases {
+            content += &format!("{} = {}\n", name, toml::quote(&version));
+        }
+    }
+
+    Ok(content)
+}
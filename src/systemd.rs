@@ -0,0 +1,167 @@
+// a user level systemd timer keeping nvim up to date without a long running watch
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{context::Context, paths};
+
+// the name both units are installed under
+static UNIT: &str = "nvim_switcher";
+
+// the environment the service needs to find the same cache, data and config as the shell
+static ENV_VARS: [&str; 3] = ["XDG_CACHE_HOME", "XDG_DATA_HOME", "XDG_CONFIG_HOME"];
+
+// get the directory the user units are read from
+fn unit_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let config = match env::var("XDG_CONFIG_HOME") {
+        Ok(dir) if !dir.is_empty() => dir,
+        _ => paths::home_dir()? + "/.config",
+    };
+
+    Ok(Path::new(&config).join("systemd/user"))
+}
+
+// quote a word of a unit file, percent signs would otherwise be read as specifiers
+fn quote(word: &str) -> String {
+    let word = word.replace('%', "%%");
+
+    if word.is_empty() || word.contains(|c: char| c.is_whitespace() || c == '"' || c == '\\') {
+        format!("\"{}\"", word.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        word
+    }
+}
+
+// run systemctl for the user, returns whether it succeeded
+fn systemctl(args: &[&str]) -> bool {
+    Command::new("systemctl")
+        .arg("--user")
+        .args(args)
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+// write the service and timer and enable the timer
+// the service checks for a new stable release, or with nightly downloads the newest nightly build
+// with switch the new stable release is also switched to, with notify a new release is shown on the desktop
+pub fn install(
+    ctx: &Context,
+    schedule: &str,
+    nightly: bool,
+    switch: bool,
+    notify: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let exe = env::current_exe()?;
+
+    // the service runs without anyone to answer a prompt
+    let mut command = vec![
+        exe.display().to_string(),
+        "--quiet".to_string(),
+        "--no-input".to_string(),
+    ];
+    if let Some(dir) = &ctx.install_dir {
+        command.push("--install-dir".to_string());
+        command.push(dir.display().to_string());
+    }
+    match (nightly, switch) {
+        (true, _) => command.extend(["download".to_string(), "nightly".to_string()]),
+        (false, true) => command.push("update".to_string()),
+        (false, false) => command.extend(["update".to_string(), "--check".to_string()]),
+    }
+    if notify {
+        command.push("--notify".to_string());
+    }
+
+    let mut service = format!(
+        "[Unit]\nDescription=Keep nvim up to date with nvim_switcher\n\n[Service]\nType=oneshot\nExecStart={}\n",
+        command
+            .iter()
+            .map(|word| quote(word))
+            .collect::<Vec<String>>()
+            .join(" ")
+    );
+
+    // update --check exits with 10 when an update is available, which is not a failure
+    if !nightly && !switch {
+        service += "SuccessExitStatus=10\n";
+    }
+
+    for var in ENV_VARS {
+        if let Ok(value) = env::var(var) {
+            if !value.is_empty() {
+                service += &format!("Environment={}\n", quote(&format!("{}={}", var, value)));
+            }
+        }
+    }
+
+    // a run missed while the machine was off is made up for at the next boot
+    let timer = format!(
+        "[Unit]\nDescription=Run nvim_switcher on a schedule\n\n[Timer]\nOnCalendar={}\nPersistent=true\n\n[Install]\nWantedBy=timers.target\n",
+        schedule
+    );
+
+    let dir = unit_dir()?;
+    paths::ensure_dir(&dir)?;
+
+    for (extension, content) in [("service", service), ("timer", timer)] {
+        let path = dir.join(format!("{}.{}", UNIT, extension));
+
+        if fs::write(&path, content).is_err() {
+            return Err(format!("Failed to write {}", path.display()).into());
+        }
+
+        ctx.reporter.info(&format!("Wrote {}", path.display()));
+    }
+
+    let timer = format!("{}.timer", UNIT);
+    if !systemctl(&["daemon-reload"]) || !systemctl(&["enable", "--now", &timer]) {
+        ctx.reporter.warn(&format!(
+            "Could not enable the timer, run `systemctl --user enable --now {}`",
+            timer
+        ));
+
+        return Ok(());
+    }
+
+    ctx.reporter
+        .success(&format!("Enabled {}, running {}", timer, schedule));
+
+    Ok(())
+}
+
+// disable the timer and remove both units
+pub fn uninstall(ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = unit_dir()?;
+    let timer = format!("{}.timer", UNIT);
+
+    let units: Vec<PathBuf> = ["service", "timer"]
+        .iter()
+        .map(|extension| dir.join(format!("{}.{}", UNIT, extension)))
+        .filter(|path| path.exists())
+        .collect();
+
+    if units.is_empty() {
+        ctx.reporter.success("The systemd units are not installed");
+
+        return Ok(());
+    }
+
+    // the timer may never have been enabled, e.g. when systemctl was not available at install
+    let _ = systemctl(&["disable", "--now", &timer]);
+
+    for path in &units {
+        if fs::remove_file(path).is_err() {
+            return Err(format!("Failed to remove {}", path.display()).into());
+        }
+
+        ctx.reporter.info(&format!("Removed {}", path.display()));
+    }
+
+    let _ = systemctl(&["daemon-reload"]);
+
+    ctx.reporter.success("Removed the systemd units");
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+// keep downloads under a given speed, so a refresh in the background leaves room on a shared connection
+// the parts of a download split over several connections share one throttle, their speed adds up
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+pub struct Throttle {
+    // the bytes per second, unlimited when not set
+    rate: Option<u64>,
+    started: Instant,
+    // the bytes counted since it started
+    counted: AtomicU64,
+}
+
+impl Throttle {
+    pub fn new(rate: Option<u64>) -> Self {
+        Throttle {
+            rate,
+            started: Instant::now(),
+            counted: AtomicU64::new(0),
+        }
+    }
+
+    // count the bytes just read, sleeping until reading them was allowed at the rate
+    pub fn wait(&self, bytes: u64) {
+        let rate = match self.rate {
+            Some(rate) => rate,
+            None => return,
+        };
+
+        let counted = self.counted.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        let allowed = Duration::from_secs_f64(counted as f64 / rate as f64);
+        if let Some(ahead) = allowed.checked_sub(self.started.elapsed()) {
+            thread::sleep(ahead);
+        }
+    }
+}
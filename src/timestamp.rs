@@ -0,0 +1,115 @@
+// conversions between unix timestamps and the rfc 3339 dates used by github
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// get the seconds since the unix epoch of a system time
+pub fn seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+// format seconds since the unix epoch as e.g. 2024-05-16T09:51:37Z
+pub fn format(seconds: u64) -> String {
+    let days = (seconds / 86400) as i64;
+    let rest = seconds % 86400;
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    )
+}
+
+// parse a duration like 30d, 12h or 2w into seconds
+pub fn parse_duration(text: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    let invalid = || format!("Invalid duration: {} (expected e.g. 30d, 12h or 2w)", text);
+
+    // split the number from its unit
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (number, unit) = text.split_at(split);
+
+    let number: u64 = number.parse().map_err(|_| invalid())?;
+    let unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        "w" => 7 * 86400,
+        _ => return Err(invalid().into()),
+    };
+
+    match number.checked_mul(unit) {
+        Some(seconds) => Ok(seconds),
+        None => Err(format!("Invalid duration: {} (the duration is too large)", text).into()),
+    }
+}
+
+// convert days since the unix epoch into a year, month and day
+// see http://howardhinnant.github.io/date_algorithms.html
+fn civil_from_days(days: i64) -> (i64, u64, u64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u64;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u64;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_are_parsed_in_every_unit() {
+        for (text, seconds) in [
+            ("45s", 45),
+            ("10m", 600),
+            ("12h", 43200),
+            ("30d", 2592000),
+            ("2w", 1209600),
+            (" 1d ", 86400),
+            ("0s", 0),
+        ] {
+            assert_eq!(parse_duration(text).unwrap(), seconds, "{}", text);
+        }
+    }
+
+    #[test]
+    fn a_duration_too_large_is_refused() {
+        let error = parse_duration("99999999999999999w").unwrap_err();
+        assert!(error.to_string().contains("too large"));
+
+        // the largest number of seconds still fits
+        assert_eq!(parse_duration(&format!("{}s", u64::MAX)).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn invalid_durations_are_refused() {
+        for text in [
+            "",
+            "30",
+            "d",
+            "30y",
+            "-1d",
+            "1.5h",
+            "30 d",
+            "1d2h",
+            "99999999999999999999s",
+        ] {
+            assert!(parse_duration(text).is_err(), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+// a small reader for the subset of toml used by the config files:
+// `key = value` pairs with strings, integers, booleans and arrays, `[section]` headers and comments
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Bool(bool),
+    Array(Vec<Value>),
+}
+
+// parse the content into a map of keys, keys in a section are prefixed like `section.key`
+pub fn parse(content: &str) -> Result<HashMap<String, Value>, Box<dyn std::error::Error>> {
+    let mut values = HashMap::new();
+
+    // the section the following keys belong to
+    let mut section = String::new();
+
+    for (number, line) in content.lines().enumerate() {
+        let line = strip_comment(line).trim();
+
+        // skip empty lines
+        if line.is_empty() {
+            continue;
+        }
+
+        // a section header
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            section = name.trim().to_string() + ".";
+
+            continue;
+        }
+
+        // a key value pair
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim().trim_matches('"'), value.trim()),
+            None => {
+                return Err(format!("Invalid line {}: {}", number + 1, line).into());
+            }
+        };
+
+        match parse_value(value) {
+            Some(value) => {
+                values.insert(section.clone() + key, value);
+            }
+            None => {
+                return Err(format!("Invalid value on line {}: {}", number + 1, value).into());
+            }
+        }
+    }
+
+    Ok(values)
+}
+
+// remove a trailing comment, a # inside of a string is kept
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+
+    for (index, c) in line.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(open)) if c == open => quote = None,
+            ('#', None) => return &line[..index],
+            _ => {}
+        }
+    }
+
+    line
+}
+
+// write a string as a basic string, the way parse reads it back
+pub fn quote(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\t', "\\t")
+    )
+}
+
+// parse a single value
+fn parse_value(value: &str) -> Option<Value> {
+    // strings
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+        {
+            return Some(Value::String(unescape(inner, quote)));
+        }
+    }
+
+    // arrays
+    if let Some(inner) = value
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+    {
+        return split_array(inner)
+            .iter()
+            .map(|item| parse_value(item.trim()))
+            .collect::<Option<Vec<Value>>>()
+            .map(Value::Array);
+    }
+
+    // booleans and integers
+    match value {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        value => value.replace('_', "").parse().ok().map(Value::Integer),
+    }
+}
+
+// undo the escapes of a basic string, literal strings are taken as is
+fn unescape(inner: &str, quote: char) -> String {
+    if quote == '\'' {
+        return inner.to_string();
+    }
+
+    let mut result = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some(c) => result.push(c),
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
+
+// split the items of an array on the commas outside of strings
+fn split_array(inner: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+
+    for (index, c) in inner.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(open)) if c == open => quote = None,
+            (',', None) => {
+                items.push(&inner[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    // the last item, allowing a trailing comma
+    if !inner[start..].trim().is_empty() {
+        items.push(&inner[start..]);
+    }
+
+    items
+}
@@ -0,0 +1,285 @@
+// a full screen dashboard of the installed and remote versions
+use std::{
+    fs,
+    io::{self, IsTerminal, Write},
+    process::{Command, Stdio},
+    rc::Rc,
+};
+
+use crate::{
+    active_version, context::Context, download, format_size, github, paths, pin, prompt,
+    prompt::Key, purge, report::CaptureReporter, report::Level, state, switch, unpin,
+};
+
+// the keys the dashboard reacts to, shown at the bottom
+static HELP: &str = "up/down move  enter/s switch  d download  p pin  x purge  r refresh  q quit";
+
+// the lines taken by everything but the list of versions
+static CHROME_LINES: usize = 5;
+
+// an action on the selected version
+type Action = fn(&Context, &str) -> Result<(), Box<dyn std::error::Error>>;
+
+// a version as shown in the list
+struct Row {
+    version: String,
+    published: Option<String>,
+    size: Option<u64>,
+    pinned: bool,
+    active: bool,
+}
+
+// everything the dashboard shows
+struct Dashboard {
+    // the tags of the releases on github with their publish date, newest first
+    remote: Vec<(String, Option<String>)>,
+    rows: Vec<Row>,
+    active: Option<String>,
+    selected: usize,
+    offset: usize,
+    message: String,
+}
+
+impl Dashboard {
+    // fetch the releases from github, the dashboard is still useful without them
+    fn fetch_remote(&mut self, ctx: &Context) {
+        match github::releases(ctx) {
+            Ok(releases) => {
+                self.remote = releases
+                    .into_iter()
+                    .map(|release| {
+                        // only the date of the publish time is of interest
+                        let date = release
+                            .published_at
+                            .as_deref()
+                            .and_then(|published| published.split('T').next())
+                            .map(|date| date.to_string());
+
+                        (release.tag, date)
+                    })
+                    .collect();
+            }
+            Err(error) => self.message = format!("Only showing the cached versions: {}", error),
+        }
+    }
+
+    // read the state of the cache again, e.g. after an action changed it
+    fn refresh(&mut self, ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+        let cached = paths::cached_versions()?;
+        let pins = state::pins()?;
+        self.active = active_version(ctx);
+
+        // the remote versions first, then the cached versions no longer on github
+        let mut versions = self.remote.clone();
+        for version in cached.iter().rev() {
+            if !versions.iter().any(|(tag, _)| tag == version) {
+                versions.push((version.clone(), None));
+            }
+        }
+
+        self.rows = versions
+            .into_iter()
+            .map(|(version, published)| Row {
+                size: cached
+                    .contains(&version)
+                    .then(|| paths::archive(&version).ok())
+                    .flatten()
+                    .and_then(|archive| fs::metadata(archive).ok())
+                    .map(|metadata| metadata.len()),
+                pinned: pins.contains(&version),
+                active: self.active.as_deref() == Some(version.as_str()),
+                published,
+                version,
+            })
+            .collect();
+
+        self.selected = self.selected.min(self.rows.len().saturating_sub(1));
+
+        Ok(())
+    }
+
+    // draw the whole screen
+    fn draw(&mut self, out: &mut impl Write, height: usize) -> io::Result<()> {
+        let visible = height.saturating_sub(CHROME_LINES).max(1);
+
+        // scroll so the selected row stays in view
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if self.selected >= self.offset + visible {
+            self.offset = self.selected + 1 - visible;
+        }
+
+        let cached: Vec<&Row> = self.rows.iter().filter(|row| row.size.is_some()).collect();
+        let total: u64 = cached.iter().filter_map(|row| row.size).sum();
+
+        // move to the top left and clear the screen
+        write!(out, "\x1b[H\x1b[2J")?;
+        write!(
+            out,
+            "nvim_switcher  active: {}  cache: {} versions, {}\r\n\r\n",
+            self.active.as_deref().unwrap_or("None"),
+            cached.len(),
+            format_size(total)
+        )?;
+
+        for (index, row) in self.rows.iter().enumerate().skip(self.offset).take(visible) {
+            let line = format!(
+                "{} {:<10} {:<10} {:>10}{}",
+                if row.active { "*" } else { " " },
+                row.version,
+                row.published.as_deref().unwrap_or(""),
+                row.size.map(format_size).unwrap_or_default(),
+                if row.pinned { "  pinned" } else { "" }
+            );
+
+            if index == self.selected {
+                write!(out, "\x1b[7m{}\x1b[0m\r\n", line)?;
+            } else {
+                write!(out, "{}\r\n", line)?;
+            }
+        }
+
+        // the message and the keys at the bottom
+        write!(
+            out,
+            "\x1b[{};1H{}\r\n\x1b[2m{}\x1b[0m",
+            height.saturating_sub(1).max(1),
+            self.message,
+            HELP
+        )?;
+
+        out.flush()
+    }
+
+    // the version of the selected row
+    fn selected(&self) -> Option<String> {
+        self.rows.get(self.selected).map(|row| row.version.clone())
+    }
+}
+
+// get the number of rows of the terminal
+fn height() -> usize {
+    Command::new("stty")
+        .arg("size")
+        .stdin(Stdio::inherit())
+        .output()
+        .ok()
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .split_whitespace()
+                .next()
+                .and_then(|rows| rows.parse().ok())
+        })
+        .filter(|rows| *rows > 0)
+        .unwrap_or(24)
+}
+
+// run an action on a version with its messages captured, the last one is shown at the bottom
+fn act(ctx: &Context, version: &str, action: Action) -> String {
+    // the screen belongs to the dashboard, a prompt would have nowhere to go
+    let capture = Rc::new(CaptureReporter::default());
+    let quiet = Context {
+        reporter: capture.clone(),
+        no_input: true,
+        ..ctx.clone()
+    };
+
+    match action(&quiet, version) {
+        Ok(()) => capture
+            .messages()
+            .into_iter()
+            .rfind(|(level, _)| *level != Level::Info)
+            .map(|(_, message)| message)
+            .unwrap_or_default(),
+        Err(error) => error.to_string(),
+    }
+}
+
+// show the dashboard until the user quits
+pub fn tui(ctx: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    // a dashboard needs a person in front of a terminal
+    if ctx.no_input || !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err("The dashboard needs a terminal".into());
+    }
+
+    let mut dashboard = Dashboard {
+        remote: Vec::new(),
+        rows: Vec::new(),
+        active: None,
+        selected: 0,
+        offset: 0,
+        message: String::new(),
+    };
+    dashboard.fetch_remote(ctx);
+    dashboard.refresh(ctx)?;
+
+    // switch to the alternate screen so the terminal is left as it was, and hide the cursor
+    let raw = prompt::RawMode::enable()?;
+    let mut stdin = io::stdin();
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b[?1049h\x1b[?25l")?;
+
+    let result = (|| -> Result<(), Box<dyn std::error::Error>> {
+        loop {
+            dashboard.draw(&mut stdout, height())?;
+
+            let key = prompt::read_key(&mut stdin)?;
+
+            // actions on the selected version
+            let action: Option<(&str, Action)> = match key {
+                Key::Up => {
+                    dashboard.selected = dashboard.selected.saturating_sub(1);
+                    None
+                }
+                Key::Down => {
+                    dashboard.selected =
+                        (dashboard.selected + 1).min(dashboard.rows.len().saturating_sub(1));
+                    None
+                }
+                Key::Char('q') | Key::Cancel => return Ok(()),
+                Key::Char('r') => {
+                    dashboard.message = String::new();
+                    dashboard.fetch_remote(ctx);
+                    None
+                }
+                Key::Enter | Key::Char('s') => Some(("Switching to", switch)),
+                Key::Char('d') => Some(("Downloading", |ctx, version| {
+                    download(ctx, version).map(|_| ())
+                })),
+                Key::Char('p') => {
+                    let pinned = dashboard
+                        .rows
+                        .get(dashboard.selected)
+                        .is_some_and(|row| row.pinned);
+
+                    if pinned {
+                        Some(("Unpinning", unpin))
+                    } else {
+                        Some(("Pinning", pin))
+                    }
+                }
+                Key::Char('x') => {
+                    Some(("Purging", |ctx, version| purge(ctx, &[version.to_string()])))
+                }
+                _ => None,
+            };
+
+            if let (Some((doing, action)), Some(version)) = (action, dashboard.selected()) {
+                // downloads take a while, show what is going on in the meantime
+                dashboard.message = format!("{} {}...", doing, version);
+                dashboard.draw(&mut stdout, height())?;
+
+                dashboard.message = act(ctx, &version, action);
+            }
+
+            dashboard.refresh(ctx)?;
+        }
+    })();
+
+    // restore the screen and the cursor
+    write!(stdout, "\x1b[?25h\x1b[?1049l")?;
+    stdout.flush()?;
+    drop(raw);
+
+    result
+}
@@ -0,0 +1,156 @@
+// check the installed files against the cached archive they were extracted from
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, Read},
+    path::{Component, Path, PathBuf},
+};
+
+use tar::EntryType;
+
+use crate::{
+    active_version, context::Context, install, manifest::Manifest, open_archive, paths, BUFFER_SIZE,
+};
+
+// read until the buffer is full or the reader is exhausted, returns the number of bytes read
+fn fill(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut read = 0;
+    while read < buffer.len() {
+        match reader.read(&mut buffer[read..])? {
+            0 => break,
+            count => read += count,
+        }
+    }
+
+    Ok(read)
+}
+
+// compare the content of an archive entry to a file
+fn same_content(entry: &mut impl Read, file: &Path) -> io::Result<bool> {
+    let mut file = BufReader::with_capacity(BUFFER_SIZE, File::open(file)?);
+    let mut expected = vec![0; BUFFER_SIZE];
+    let mut actual = vec![0; BUFFER_SIZE];
+
+    loop {
+        let read = fill(entry, &mut expected)?;
+        if fill(&mut file, &mut actual)? != read || expected[..read] != actual[..read] {
+            return Ok(false);
+        }
+
+        if read == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+// report the files of the installed version that are missing or differ from its archive
+// with fix the version is extracted again when any are found
+pub fn verify(ctx: &Context, fix: bool) -> Result<(), Box<dyn std::error::Error>> {
+    // the manifest still knows the version when the nvim binary itself is gone
+    let version = match active_version(ctx).or_else(|| Some(Manifest::load().ok()??.version)) {
+        Some(version) => version,
+        None => return Err("No version is installed".into()),
+    };
+
+    // the archive is the record of what the install should hold
+    let archive = paths::archive(&version)?;
+    if !archive.exists() {
+        return Err(format!(
+            "Version {} is no longer cached, download it to verify the install against it",
+            version
+        )
+        .into());
+    }
+
+    ctx.reporter.info(&format!("Verifying version {}", version));
+
+    let dir = paths::output_dir(ctx)?;
+    let mut checked = 0;
+    let mut missing: Vec<PathBuf> = Vec::new();
+    let mut modified: Vec<PathBuf> = Vec::new();
+
+    for entry in open_archive(&archive)?.entries()? {
+        let mut entry = entry?;
+
+        // the same paths extract produced, without the top level directory
+        let path: PathBuf = entry.path()?.components().skip(1).collect();
+        if path.as_os_str().is_empty()
+            || path
+                .components()
+                .any(|component| !matches!(component, Component::Normal(_)))
+        {
+            continue;
+        }
+
+        let target = dir.join(&path);
+        let metadata = match fs::symlink_metadata(&target) {
+            Ok(metadata) => metadata,
+            Err(_) => {
+                missing.push(path);
+                continue;
+            }
+        };
+
+        let intact = match entry.header().entry_type() {
+            EntryType::Directory => metadata.is_dir(),
+            EntryType::Symlink => {
+                metadata.file_type().is_symlink()
+                    && fs::read_link(&target).ok() == entry.link_name()?.map(|link| link.into())
+            }
+            EntryType::Regular => {
+                metadata.is_file()
+                    && metadata.len() == entry.size()
+                    && same_content(&mut entry, &target)?
+            }
+            // nothing else is part of a release
+            _ => continue,
+        };
+
+        checked += 1;
+        if !intact {
+            modified.push(path);
+        }
+    }
+
+    for path in &missing {
+        ctx.reporter.warn(&format!("Missing: {}", path.display()));
+    }
+    for path in &modified {
+        ctx.reporter.warn(&format!("Modified: {}", path.display()));
+    }
+
+    let broken = missing.len() + modified.len();
+    if broken == 0 {
+        ctx.reporter.success(&format!(
+            "All {} files of version {} are intact",
+            checked, version
+        ));
+
+        return Ok(());
+    }
+
+    // the cached nightly is replaced by every download, not only by a switch
+    if version == "nightly" {
+        ctx.reporter.info(
+            "A newer nightly build downloaded since switching to it also shows up as modified files",
+        );
+    }
+
+    if fix {
+        ctx.reporter
+            .info(&format!("Extracting version {} again", version));
+        install(ctx, &version)?;
+
+        ctx.reporter
+            .success(&format!("Reinstalled version {}", version));
+
+        return Ok(());
+    }
+
+    Err(format!(
+        "{} of {} files of version {} are missing or modified, run verify --fix to extract it again",
+        broken,
+        checked + missing.len(),
+        version
+    )
+    .into())
+}
@@ -0,0 +1,401 @@
+use std::cmp::Ordering;
+
+use crate::{
+    context::Context,
+    github, paths,
+    requirement::{self, Requirement},
+    state,
+};
+
+// tags that do not follow the version number format, in the order they sort after numbered versions
+// stable and latest are keywords for the newest release, nightly is a tag published upstream
+pub static NAMED_TAGS: [&str; 3] = ["stable", "latest", "nightly"];
+
+// the repository of neovim itself, its versions are not qualified
+pub static UPSTREAM_REPO: &str = "neovim/neovim";
+
+// split a version of a fork into its repository and tag, e.g. owner/neovim@v0.10.0
+// versions of neovim itself have no repository
+pub fn split_repo(version: &str) -> (Option<&str>, &str) {
+    match version.rsplit_once('@') {
+        Some((repo, tag)) if repo.contains('/') => (Some(repo), tag),
+        _ => (None, version),
+    }
+}
+
+// name the tag of a repository as a version, the tags of neovim itself stay as they are
+pub fn qualify(repo: Option<&str>, tag: &str) -> String {
+    match repo {
+        Some(repo) if repo != UPSTREAM_REPO => format!("{}@{}", repo, tag),
+        _ => tag.to_string(),
+    }
+}
+
+// is the repository of the form owner/name, it becomes part of the paths in the cache
+pub fn is_valid_repo(repo: &str) -> bool {
+    let parts: Vec<&str> = repo.split('/').collect();
+
+    parts.len() == 2
+        && parts.iter().all(|part| {
+            !part.is_empty()
+                && !part.starts_with('.')
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+        })
+}
+
+// resolve a user supplied version into the tag to download
+// keywords like stable, partial versions like 0.10 and requirements like `>=0.9, <0.11`
+// are looked up on github, aliases are replaced by their version and everything else only
+// needs normalizing
+pub fn resolve_version(ctx: &Context, version: &str) -> Result<String, Box<dyn std::error::Error>> {
+    // an alias stands for the version it was created with, which is resolved every time
+    // so an alias of stable follows new releases
+    match state::alias(version.trim())? {
+        Some(target) => resolve(ctx, &target),
+        None => resolve(ctx, version),
+    }
+}
+
+// can the name be used as an alias, it must not be mistaken for a version
+pub fn is_valid_alias(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && name.chars().any(|c| c.is_ascii_alphabetic())
+        && normalize(name).is_err()
+        && partial(name).is_none()
+}
+
+// is the version a snapshot of an earlier nightly build, named after the day it was downloaded
+// e.g. nightly-2024-06-01
+pub fn is_snapshot(version: &str) -> bool {
+    version.strip_prefix("nightly-").is_some_and(|date| {
+        date.len() == 10
+            && date.char_indices().all(|(index, c)| match index {
+                4 | 7 => c == '-',
+                _ => c.is_ascii_digit(),
+            })
+    })
+}
+
+// resolve a version that is not an alias
+fn resolve(ctx: &Context, version: &str) -> Result<String, Box<dyn std::error::Error>> {
+    // a version of a fork is looked up in the releases of the fork
+    if let (Some(repo), tag) = split_repo(version.trim()) {
+        if !is_valid_repo(repo) {
+            return Err(format!("Invalid repository: {} (expected owner/name)", repo).into());
+        }
+
+        let ctx = Context {
+            repo: Some(repo.to_string()).filter(|repo| repo != UPSTREAM_REPO),
+            ..ctx.clone()
+        };
+
+        return resolve(&ctx, tag);
+    }
+
+    // a requirement matches the newest release satisfying it
+    if requirement::is_requirement(version) {
+        let requirement = Requirement::parse(version)?;
+
+        return match newest(ctx, |number| requirement.matches(number))? {
+            Some(tag) => Ok(tag),
+            None => Err(format!("No release satisfying {} found", version.trim()).into()),
+        };
+    }
+
+    // a partial version matches the newest release starting with it
+    if let Some(prefix) = partial(version) {
+        return match newest(ctx, |number| number.starts_with(&prefix))? {
+            Some(tag) => Ok(tag),
+            None => Err(format!("No release matching {} found", version.trim()).into()),
+        };
+    }
+
+    // normalize the version into the upstream tag
+    let version = normalize(version)?;
+
+    match version.as_str() {
+        "stable" | "latest" => match newest(ctx, |_| true)? {
+            Some(tag) => Ok(tag),
+            None => Err("No stable release found".into()),
+        },
+        _ => Ok(qualify(ctx.repo.as_deref(), &version)),
+    }
+}
+
+// get the tag of the newest numbered release that is not a prerelease and matches
+// offline only the cached versions of the repository are looked at
+fn newest(
+    ctx: &Context,
+    matches: impl Fn(&[u64; 3]) -> bool,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let tags: Vec<String> = if ctx.offline.get() {
+        paths::cached_versions()?
+            .into_iter()
+            .filter(|version| split_repo(version).0 == ctx.repo.as_deref())
+            .collect()
+    } else {
+        github::releases(ctx)?
+            .into_iter()
+            .filter(|release| !release.prerelease)
+            .map(|release| release.tag)
+            .collect()
+    };
+
+    Ok(tags
+        .into_iter()
+        .filter_map(|tag| number(&tag).map(|number| (number, tag)))
+        .filter(|(number, _)| matches(number))
+        .max()
+        .map(|(_, tag)| tag))
+}
+
+// get the numeric parts of a version missing its patch or minor part, e.g. 0.10 becomes [0, 10]
+pub fn partial(version: &str) -> Option<Vec<u64>> {
+    // strip the optional v prefix
+    let version = version.trim().trim_start_matches(['v', 'V']);
+
+    // parse the parts, a complete version is not partial
+    let parts = version
+        .split('.')
+        .map(|part| {
+            part.parse::<u64>()
+                .ok()
+                .filter(|_| part.chars().all(|c| c.is_ascii_digit()))
+        })
+        .collect::<Option<Vec<u64>>>()?;
+
+    match parts.len() {
+        1 | 2 => Some(parts),
+        _ => None,
+    }
+}
+
+// normalize a user supplied version into the canonical tag used upstream
+// both `0.10.0` and `v0.10.0` map to the tag `v0.10.0`
+pub fn normalize(version: &str) -> Result<String, Box<dyn std::error::Error>> {
+    // ignore surrounding whitespace and casing of the prefix
+    let version = version.trim();
+
+    // the tag of a fork is normalized the same way
+    if let (Some(repo), tag) = split_repo(version) {
+        if !is_valid_repo(repo) {
+            return Err(format!("Invalid repository: {} (expected owner/name)", repo).into());
+        }
+
+        return Ok(qualify(Some(repo), &normalize(tag)?));
+    }
+
+    // snapshots of earlier nightly builds are used as is
+    if is_snapshot(version) {
+        return Ok(version.to_string());
+    }
+
+    // named tags are used as is
+    if let Some(tag) = NAMED_TAGS
+        .iter()
+        .find(|tag| tag.eq_ignore_ascii_case(version))
+    {
+        return Ok(tag.to_string());
+    }
+
+    // strip the optional v prefix
+    let number = version
+        .strip_prefix('v')
+        .or_else(|| version.strip_prefix('V'))
+        .unwrap_or(version);
+
+    // the tag must consist of exactly three numeric parts
+    let parts: Vec<&str> = number.split('.').collect();
+    if parts.len() != 3
+        || parts
+            .iter()
+            .any(|part| part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()))
+    {
+        return Err(format!(
+            "Invalid version: {} (expected a tag like v0.10.0, 0.10, stable, latest or nightly)",
+            version
+        )
+        .into());
+    }
+
+    Ok(format!("v{}", number))
+}
+
+// get the numeric parts of a version, e.g. v0.10.0 or 0.10.0 becomes [0, 10, 0]
+// the repository of a fork is ignored
+pub fn number(version: &str) -> Option<[u64; 3]> {
+    // strip the optional v prefix
+    let version = split_repo(version.trim()).1.trim_start_matches(['v', 'V']);
+
+    // parse the three parts
+    let mut parts = version.split('.').map(|part| part.parse::<u64>().ok());
+    let number = [parts.next()??, parts.next()??, parts.next()??];
+
+    // there should be nothing left
+    match parts.next() {
+        Some(_) => None,
+        None => Some(number),
+    }
+}
+
+// does the version given by the user name the active one, decided without asking github
+// stable, partial versions and requirements match the active version when none of the known versions,
+// e.g. those cached or seen in the last list of releases, is a newer match
+pub fn is_active(version: &str, active: &str, known: &[String]) -> bool {
+    // the fork of a version, none for neovim itself however it is named
+    fn in_repo(version: &str) -> Option<&str> {
+        split_repo(version).0.filter(|repo| *repo != UPSTREAM_REPO)
+    }
+
+    let (repo, tag) = split_repo(version.trim());
+    if repo.filter(|repo| *repo != UPSTREAM_REPO) != in_repo(active) {
+        return false;
+    }
+
+    // the newest match is active when nothing newer matching it is known
+    let newest = |matches: &dyn Fn(&[u64; 3]) -> bool| match number(active) {
+        Some(active_number) => {
+            matches(&active_number)
+                && !known
+                    .iter()
+                    .filter(|version| in_repo(version) == in_repo(active))
+                    .filter_map(|version| number(version))
+                    .any(|number| number > active_number && matches(&number))
+        }
+        None => false,
+    };
+
+    if requirement::is_requirement(tag) {
+        return match Requirement::parse(tag) {
+            Ok(requirement) => newest(&|number| requirement.matches(number)),
+            Err(_) => false,
+        };
+    }
+    if let Some(prefix) = partial(tag) {
+        return newest(&|number| number.starts_with(&prefix));
+    }
+
+    match normalize(tag).as_deref() {
+        Ok("stable" | "latest") => newest(&|_| true),
+        Ok(tag) => tag == split_repo(active).1,
+        Err(_) => false,
+    }
+}
+
+// order versions from old to new
+// numbered versions come first, followed by the named tags in the order stable, latest, nightly
+pub fn compare(a: &str, b: &str) -> Ordering {
+    // the position of a named tag, numbered versions sort before all of them
+    let rank = |version: &str| {
+        NAMED_TAGS
+            .iter()
+            .position(|tag| *tag == split_repo(version).1)
+    };
+
+    // the same version of neovim and of a fork are told apart by name
+    match (number(a), number(b)) {
+        (Some(first), Some(second)) => first.cmp(&second).then_with(|| a.cmp(b)),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => rank(a).cmp(&rank(b)).then_with(|| a.cmp(b)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_are_normalized_into_tags() {
+        for (version, tag) in [
+            ("0.10.0", "v0.10.0"),
+            ("v0.9.5", "v0.9.5"),
+            ("V0.9.5", "v0.9.5"),
+            (" 0.10.0\n", "v0.10.0"),
+            ("stable", "stable"),
+            ("Latest", "latest"),
+            ("NIGHTLY", "nightly"),
+            ("nightly-2024-06-01", "nightly-2024-06-01"),
+            ("someone/neovim@0.10.0", "someone/neovim@v0.10.0"),
+            ("neovim/neovim@0.10.0", "v0.10.0"),
+        ] {
+            assert_eq!(normalize(version).unwrap(), tag, "{:?}", version);
+        }
+    }
+
+    #[test]
+    fn partial_versions_are_left_to_resolve() {
+        // 0.10 is not a tag, it resolves to the newest release starting with it, e.g. v0.10.4
+        assert!(normalize("0.10").is_err());
+        assert_eq!(partial("0.10"), Some(vec![0, 10]));
+        assert_eq!(partial("v1"), Some(vec![1]));
+        assert_eq!(partial("0.10.0"), None);
+        assert_eq!(partial("0.x"), None);
+    }
+
+    #[test]
+    fn invalid_versions_are_refused() {
+        for version in [
+            "",
+            "v",
+            "vv0.10.0",
+            "0.10.0.1",
+            "0..1",
+            "0.10.x",
+            "-1.0.0",
+            "+1.0.0",
+            "0.11.0-dev",
+            "garbage",
+            "nightly-2024-6-1",
+            "a/b/c@v0.10.0",
+            "/neovim@v0.10.0",
+        ] {
+            assert!(normalize(version).is_err(), "{:?}", version);
+        }
+    }
+
+    fn known(versions: &[&str]) -> Vec<String> {
+        versions.iter().map(|version| version.to_string()).collect()
+    }
+
+    #[test]
+    fn is_active_matches_named_tags_by_name() {
+        let known = known(&["v0.9.5", "v0.10.0"]);
+
+        assert!(is_active("nightly", "nightly", &known));
+        assert!(is_active("Nightly", "nightly", &known));
+        assert!(!is_active("nightly", "v0.10.0", &known));
+        assert!(is_active("0.10.0", "v0.10.0", &known));
+        assert!(!is_active("v0.9.5", "v0.10.0", &known));
+    }
+
+    #[test]
+    fn is_active_matches_the_newest_known_release() {
+        let known = known(&["v0.9.5", "v0.10.0", "v0.10.1", "nightly"]);
+
+        assert!(is_active("stable", "v0.10.1", &known));
+        assert!(!is_active("stable", "v0.10.0", &known));
+        assert!(!is_active("stable", "nightly", &known));
+        assert!(is_active("0.9", "v0.9.5", &known));
+        assert!(!is_active("0.10", "v0.10.0", &known));
+        assert!(is_active(">=0.9, <0.10", "v0.9.5", &known));
+    }
+
+    #[test]
+    fn is_active_tells_forks_apart() {
+        let known = known(&["v0.10.0", "someone/neovim@v0.10.0"]);
+
+        assert!(is_active(
+            "someone/neovim@v0.10.0",
+            "someone/neovim@v0.10.0",
+            &known
+        ));
+        assert!(!is_active("v0.10.0", "someone/neovim@v0.10.0", &known));
+        assert!(is_active("neovim/neovim@v0.10.0", "v0.10.0", &known));
+    }
+}
@@ -0,0 +1,46 @@
+// keep the nightly build fresh by checking for a new one periodically
+use std::{fs, rc::Rc, thread, time::Duration};
+
+use crate::{context::Context, download, paths, report::TimestampReporter, switch};
+
+// check for a new nightly build every interval until stopped, downloading it when there is one
+// with switch the new build is also switched to
+pub fn watch(
+    ctx: &Context,
+    interval: u64,
+    switch_to: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // the output of a long running command is usually kept as a log, so every line gets the time
+    let ctx = &Context {
+        reporter: Rc::new(TimestampReporter {
+            inner: ctx.reporter.clone(),
+        }),
+        ..ctx.clone()
+    };
+
+    ctx.reporter.info(&format!(
+        "Checking for a new nightly build every {} seconds",
+        interval
+    ));
+
+    loop {
+        // the revision changes whenever a different build is downloaded
+        let before = fs::read_to_string(paths::revision("nightly")?).ok();
+
+        // a failed check is retried at the next interval
+        match download(ctx, "nightly") {
+            Ok(_) => {
+                let after = fs::read_to_string(paths::revision("nightly")?).ok();
+
+                if before != after && switch_to {
+                    if let Err(error) = switch(ctx, "nightly") {
+                        ctx.reporter.error(&error.to_string());
+                    }
+                }
+            }
+            Err(error) => ctx.reporter.error(&error.to_string()),
+        }
+
+        thread::sleep(Duration::from_secs(interval));
+    }
+}